@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use common::{Diagnostics, Part, Solver};
+
+use crate::render;
+
+/// Summary of repeated timings of a single stage.
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Stats {
+        samples.sort_unstable();
+
+        let count = samples.len();
+        let middle = count / 2;
+        let median = if count.is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+
+        let total: Duration = samples.iter().sum();
+
+        Stats {
+            min: samples[0],
+            median,
+            mean: total / count as u32,
+            max: samples[count - 1],
+        }
+    }
+}
+
+/// Timings for one stage of a puzzle: parsing its input or solving a part.
+pub struct Measurement {
+    pub day: &'static str,
+    pub stage: String,
+    pub stats: Stats,
+}
+
+/// Times parsing and each part of every puzzle separately, `iterations` times
+/// each, using the preset input. Only the final, untimed parse reports to
+/// `diagnostics` so warnings are not repeated for every iteration.
+pub fn run(puzzles: &[&'static dyn Solver], iterations: u32, diagnostics: &mut Diagnostics) -> Result<Vec<Measurement>, Box<dyn Error>> {
+    if iterations == 0 {
+        return Err("'Bench' parameter must be at least 1".into());
+    }
+
+    let mut measurements = Vec::new();
+
+    for puzzle in puzzles {
+        let input = puzzle.input()?;
+
+        let samples = sample(iterations, || {
+            let mut diagnostics = Diagnostics::new(diagnostics.strictness());
+            puzzle.parse(&input, &mut diagnostics).map(|_| ())
+        })?;
+        measurements.push(Measurement { day: puzzle.name(), stage: String::from("parse"), stats: Stats::new(samples) });
+
+        let data = puzzle.parse(&input, diagnostics)?;
+        for part in &[Part::One, Part::Two] {
+            let samples = sample(iterations, || puzzle.solve(*part, data.as_ref()).map(|_| ()))?;
+            let stage = format!("part {}", render::part(*part));
+            measurements.push(Measurement { day: puzzle.name(), stage, stats: Stats::new(samples) });
+        }
+    }
+
+    Ok(measurements)
+}
+
+fn sample<F>(iterations: u32, mut f: F) -> Result<Vec<Duration>, common::Error>
+where F: FnMut() -> Result<(), common::Error> {
+    let mut samples = Vec::new();
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        samples.push(start.elapsed());
+    }
+
+    Ok(samples)
+}
+
+pub fn table(measurements: &[Measurement]) -> String {
+    let rows: Vec<Vec<String>> = measurements.iter()
+        .map(|m| vec![
+            String::from(m.day),
+            m.stage.clone(),
+            render::duration(m.stats.min),
+            render::duration(m.stats.median),
+            render::duration(m.stats.mean),
+            render::duration(m.stats.max),
+        ])
+        .collect();
+
+    render::table(&["Day", "Stage", "Min", "Median", "Mean", "Max"], &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn stats_new_should_calculate_odd_samples() {
+        // arrange
+        let samples = millis(&[5, 1, 3]);
+
+        // act
+        let stats = Stats::new(samples);
+
+        // assert
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_millis(3), stats.median);
+        assert_eq!(Duration::from_millis(3), stats.mean);
+        assert_eq!(Duration::from_millis(5), stats.max);
+    }
+
+    #[test]
+    fn stats_new_should_average_middle_of_even_samples() {
+        // arrange
+        let samples = millis(&[4, 1, 2, 9]);
+
+        // act
+        let stats = Stats::new(samples);
+
+        // assert
+        assert_eq!(Duration::from_millis(3), stats.median);
+        assert_eq!(Duration::from_millis(4), stats.mean);
+    }
+}
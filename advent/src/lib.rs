@@ -1,15 +1,324 @@
 use std::error::Error;
+use std::io::{self, Write};
 
-use common::{self, Config};
-use day1;
-use day2;
-use day3;
+use common::{Args, Config, Diagnostics, Registry, Solver, Strictness};
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    match config.puzzle_name.as_str() {
-        "day1" => day1::run(config),
-        "day2" => day2::run(config),
-        "day3" => day3::run(config),
-        _ => Err("Unknown puzzle name")?,
+mod bench;
+mod render;
+mod summary;
+mod verify;
+
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+
+    day1::register(&mut registry);
+    day2::register(&mut registry);
+    day3::register(&mut registry);
+
+    registry
+}
+
+pub fn run(mut config: Config) -> Result<(), Box<dyn Error>> {
+    let registry = registry();
+    let mut diagnostics = Diagnostics::new(strictness(&mut config)?);
+
+    let result = dispatch(&registry, config, &mut diagnostics);
+
+    for warning in diagnostics.warnings() {
+        eprintln!("warning: {}", warning.render());
+    }
+
+    result
+}
+
+/// Describes an error for the user, showing the offending line of input for
+/// parse errors.
+pub fn describe(err: &(dyn Error + 'static)) -> String {
+    match err.downcast_ref::<common::Error>() {
+        Some(common::Error::Parse(diagnostic)) => diagnostic.render(),
+        _ => err.to_string(),
+    }
+}
+
+/// Takes the `--strict` and `--lenient` switches given before the command,
+/// returning the last one. Arguments after the command belong to it.
+fn strictness(config: &mut Config) -> Result<Strictness, common::Error> {
+    let mut strictness = Strictness::default();
+
+    loop {
+        match config.command.as_str() {
+            "--strict" => strictness = Strictness::Strict,
+            "--lenient" => strictness = Strictness::Lenient,
+            _ => return Ok(strictness),
+        }
+
+        if config.args.is_empty() {
+            return Err(common::Error::MissingArgument("Command"));
+        }
+        config.command = config.args.remove(0);
+    }
+}
+
+const USAGE: &str = "\
+Usage: advent [--strict | --lenient] <COMMAND> [ARGS]
+
+Commands:
+  run <PUZZLE> [ARGS]    Run a puzzle; `advent run <PUZZLE> --help` lists its parameters
+  <PUZZLE> [ARGS]        Shorthand for `run <PUZZLE>`
+  all                    Run both parts of every puzzle and print a summary table
+  <FROM>..<TO>           Run both parts of the puzzles from FROM to TO inclusive
+  verify [FILE]          Check answers against FILE, by default answers.toml
+  list                   List every puzzle
+  help                   Print this help
+
+Options:
+  --bench <N>            After `all`, a range or a puzzle, time each stage N times
+  --strict               Before the command, stop at the first input line that fails to parse (default)
+  --lenient              Before the command, skip input lines that fail to parse, printing them as warnings";
+
+fn dispatch(registry: &Registry, config: Config, diagnostics: &mut Diagnostics) -> Result<(), Box<dyn Error>> {
+    let Config { mut command, mut args } = config;
+
+    if command == "run" {
+        if args.is_empty() {
+            return Err(common::Error::MissingArgument("Puzzle name").into());
+        }
+        command = args.remove(0);
+    }
+
+    match command.as_str() {
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return Ok(());
+        },
+        "list" => {
+            let width = registry.iter().map(|p| p.name().len()).max().unwrap_or(0);
+            for puzzle in registry.iter() {
+                println!("{:width$}  {}", puzzle.name(), puzzle.about(), width = width);
+            }
+            return Ok(());
+        },
+        "verify" => {
+            let filename = args.first().map(|a| a.as_str()).unwrap_or("answers.toml");
+            let cases = verify::load(filename)?;
+            return run_verify(registry, cases, diagnostics);
+        },
+        _ => (),
+    }
+
+    if args.first().map(|a| a.as_str()) == Some("--bench") {
+        let iterations = match args.get(1) {
+            Some(arg) => arg.parse::<u32>().map_err(|e| common::Error::bad_integer("Bench", arg, e))?,
+            None => return Err(common::Error::MissingArgument("Bench").into()),
+        };
+
+        let puzzles = select(registry, &command)?;
+        let measurements = bench::run(&puzzles, iterations, diagnostics)?;
+        println!("{}", bench::table(&measurements));
+        return Ok(());
+    }
+
+    if command == "all" || command.contains("..") {
+        let puzzles = select(registry, &command)?;
+        return run_all(&puzzles, diagnostics);
+    }
+
+    let puzzle = find(registry, &command)?;
+    let args = Args::parse(puzzle.params(), args)?;
+
+    if args.help() {
+        println!("{}", common::help(puzzle.name(), puzzle.about(), puzzle.params()));
+        return Ok(());
+    }
+
+    let answer = puzzle.run(&args, diagnostics)?;
+
+    // Reports may be piped into a reader such as `head` that stops early
+    match writeln!(io::stdout(), "{}", render::answer(&answer)) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+fn find(registry: &Registry, name: &str) -> Result<&'static dyn Solver, Box<dyn Error>> {
+    match registry.find(name) {
+        Some(puzzle) => Ok(puzzle),
+        None => Err(format!("Unknown puzzle '{}', use `advent list` to see every puzzle", name).into()),
+    }
+}
+
+/// Resolves a puzzle name, `all`, or an inclusive range such as `day1..day3`
+/// to the matching puzzles, in registration order.
+fn select(registry: &Registry, spec: &str) -> Result<Vec<&'static dyn Solver>, Box<dyn Error>> {
+    let puzzles: Vec<&'static dyn Solver> = registry.iter().collect();
+
+    if spec == "all" {
+        return Ok(puzzles);
+    }
+
+    if !spec.contains("..") {
+        return Ok(vec![find(registry, spec)?]);
+    }
+
+    let mut bounds = spec.splitn(2, "..");
+    let start = bounds.next().unwrap_or_default();
+    let end = bounds.next().unwrap_or_default();
+
+    let position = |name: &str| puzzles.iter().position(|p| p.name() == name);
+
+    let start = if start.is_empty() { Some(0) } else { position(start) };
+    let end = if end.is_empty() { Some(puzzles.len() - 1) } else { position(end) };
+
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end => Ok(puzzles[start..=end].to_vec()),
+        (Some(_), Some(_)) => Err("Puzzle range is empty".into()),
+        _ => Err("Unknown puzzle name in range".into()),
+    }
+}
+
+fn run_all(puzzles: &[&'static dyn Solver], diagnostics: &mut Diagnostics) -> Result<(), Box<dyn Error>> {
+    let outcomes = summary::run(puzzles, diagnostics);
+    println!("{}", summary::table(&outcomes));
+
+    let failures = summary::failures(&outcomes);
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    for failure in &failures {
+        println!("{}", failure);
+    }
+
+    Err(format!("{} puzzle part(s) failed", failures.len()).into())
+}
+
+fn run_verify(registry: &Registry, cases: Vec<verify::Case>, diagnostics: &mut Diagnostics) -> Result<(), Box<dyn Error>> {
+    let checks = verify::run(registry, cases, diagnostics);
+    println!("{}", verify::table(&checks));
+
+    let failures = verify::failures(&checks);
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    for failure in &failures {
+        println!("{}", failure);
+    }
+
+    Err(format!("{} of {} case(s) failed", failures.len(), checks.len()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_should_contain_every_day() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let names: Vec<&str> = registry.iter().map(|p| p.name()).collect();
+
+        // assert
+        assert_eq!(vec!["day1", "day2", "day3"], names);
+    }
+
+    fn names(puzzles: Vec<&'static dyn Solver>) -> Vec<&'static str> {
+        puzzles.iter().map(|p| p.name()).collect()
+    }
+
+    fn config(args: &[&str]) -> Config {
+        Config::new(args.iter().map(|a| String::from(*a))).unwrap()
+    }
+
+    #[test]
+    fn strictness_should_take_switches_before_command() {
+        // arrange
+        let mut config = config(&["advent", "--lenient", "--strict", "day1", "day1/data.txt"]);
+
+        // act
+        let strictness = strictness(&mut config).unwrap();
+
+        // assert
+        assert_eq!(Strictness::Strict, strictness);
+        assert_eq!("day1", config.command);
+        assert_eq!(vec!["day1/data.txt"], config.args);
+    }
+
+    #[test]
+    fn strictness_should_leave_puzzle_arguments() {
+        // arrange
+        let mut config = config(&["advent", "day2", "--lenient", "--strict"]);
+
+        // act
+        let strictness = strictness(&mut config).unwrap();
+
+        // assert
+        assert_eq!(Strictness::default(), strictness);
+        assert_eq!("day2", config.command);
+        assert_eq!(vec!["--lenient", "--strict"], config.args);
+    }
+
+    #[test]
+    fn select_should_return_all() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let puzzles = select(&registry, "all").unwrap();
+
+        // assert
+        assert_eq!(vec!["day1", "day2", "day3"], names(puzzles));
+    }
+
+    #[test]
+    fn select_should_return_inclusive_range() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let puzzles = select(&registry, "day2..day3").unwrap();
+
+        // assert
+        assert_eq!(vec!["day2", "day3"], names(puzzles));
+    }
+
+    #[test]
+    fn select_should_allow_open_range() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let puzzles = select(&registry, "..day2").unwrap();
+
+        // assert
+        assert_eq!(vec!["day1", "day2"], names(puzzles));
+    }
+
+    #[test]
+    fn select_should_return_single_puzzle() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let puzzles = select(&registry, "day3").unwrap();
+
+        // assert
+        assert_eq!(vec!["day3"], names(puzzles));
+    }
+
+    #[test]
+    fn select_should_reject_unknown_names() {
+        // arrange
+        let registry = registry();
+
+        // act
+        let result = select(&registry, "day1..day99");
+
+        // assert
+        assert!(result.is_err());
     }
-}
\ No newline at end of file
+}
@@ -6,11 +6,12 @@ use common::Config;
 fn main() {
     let config = Config::new(env::args()).unwrap_or_else(|err| {
         eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("Use `advent help` to see every command");
         process::exit(1);
     });
 
     if let Err(e) = advent::run(config) {
-        eprintln!("Application error: {}", e);
+        eprintln!("Application error: {}", advent::describe(e.as_ref()));
         process::exit(1);
     }
 }
\ No newline at end of file
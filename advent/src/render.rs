@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use common::{Answer, Part, Value};
+
+pub use common::table;
+
+pub fn value(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i.to_string(),
+        Value::Text(text) => text.clone(),
+        Value::List(list) => {
+            let items: Vec<String> = list.iter().map(|i| i.to_string()).collect();
+            format!("[{}]", items.join(", "))
+        },
+    }
+}
+
+/// Renders the answer followed by each labelled detail on its own line.
+pub fn answer(answer: &Answer) -> String {
+    let mut text = value(&answer.value);
+
+    for (label, detail) in &answer.details {
+        text.push_str(&format!("\n  {}: {}", label, value(detail)));
+    }
+
+    text
+}
+
+pub fn part(part: Part) -> String {
+    match part {
+        Part::One => String::from("1"),
+        Part::Two => String::from("2"),
+    }
+}
+
+pub fn duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_should_render_details() {
+        // arrange
+        let answer = Answer::new(6)
+            .with("Numbers", vec![2, 3])
+            .with("Name", "six");
+
+        // act
+        let text = super::answer(&answer);
+
+        // assert
+        assert_eq!("6\n  Numbers: [2, 3]\n  Name: six", text);
+    }
+}
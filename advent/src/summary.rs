@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use common::{Answer, Diagnostics, Part, Solver};
+
+use crate::render;
+
+/// Result of solving one part of a puzzle.
+pub struct Outcome {
+    pub day: &'static str,
+    pub part: Part,
+    pub answer: Result<Answer, String>,
+    pub elapsed: Duration,
+}
+
+/// Solves both parts of every puzzle using its preset input, carrying on past
+/// any part that fails.
+pub fn run(puzzles: &[&'static dyn Solver], diagnostics: &mut Diagnostics) -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+
+    for puzzle in puzzles {
+        for part in &[Part::One, Part::Two] {
+            let start = Instant::now();
+            let answer = solve(*puzzle, *part, diagnostics).map_err(|e| e.to_string());
+            let elapsed = start.elapsed();
+
+            outcomes.push(Outcome { day: puzzle.name(), part: *part, answer, elapsed });
+        }
+    }
+
+    outcomes
+}
+
+fn solve(puzzle: &dyn Solver, part: Part, diagnostics: &mut Diagnostics) -> Result<Answer, common::Error> {
+    let data = puzzle.parse(&puzzle.input()?, diagnostics)?;
+    puzzle.solve(part, data.as_ref())
+}
+
+pub fn table(outcomes: &[Outcome]) -> String {
+    let rows: Vec<Vec<String>> = outcomes.iter()
+        .map(|o| vec![
+            String::from(o.day),
+            render::part(o.part),
+            match &o.answer {
+                Ok(answer) => render::value(&answer.value),
+                Err(_) => String::from("FAILED"),
+            },
+            render::duration(o.elapsed),
+        ])
+        .collect();
+
+    render::table(&["Day", "Part", "Answer", "Time"], &rows)
+}
+
+pub fn failures(outcomes: &[Outcome]) -> Vec<String> {
+    outcomes.iter()
+        .filter_map(|o| match &o.answer {
+            Ok(_) => None,
+            Err(err) => Some(format!("{} part {}: {}", o.day, render::part(o.part), err)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(part: Part, answer: Result<Answer, String>) -> Outcome {
+        Outcome { day: "day1", part, answer, elapsed: Duration::from_millis(2) }
+    }
+
+    #[test]
+    fn table_should_mark_failures() {
+        // arrange
+        let outcomes = vec![
+            outcome(Part::One, Ok(Answer::new(42))),
+            outcome(Part::Two, Err(String::from("broken"))),
+        ];
+
+        // act
+        let table = table(&outcomes);
+
+        // assert
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!("Day  | Part | Answer | Time", lines[0]);
+        assert_eq!("day1 | 1    | 42     | 2.000ms", lines[2]);
+        assert_eq!("day1 | 2    | FAILED | 2.000ms", lines[3]);
+    }
+
+    #[test]
+    fn failures_should_list_errors() {
+        // arrange
+        let outcomes = vec![
+            outcome(Part::One, Ok(Answer::new(42))),
+            outcome(Part::Two, Err(String::from("broken"))),
+        ];
+
+        // act
+        let failures = failures(&outcomes);
+
+        // assert
+        assert_eq!(vec!["day1 part 2: broken"], failures);
+    }
+}
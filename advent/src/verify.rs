@@ -0,0 +1,206 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use common::{Diagnostics, Input, Part, Registry, Value};
+
+use crate::render;
+
+/// Contents of an answers file, listing the known-good answer of each case.
+#[derive(Deserialize)]
+struct Answers {
+    #[serde(default)]
+    answer: Vec<Case>,
+}
+
+/// A single regression case. `input` defaults to the puzzle's preset input,
+/// and is otherwise relative to the directory of the answers file.
+#[derive(Deserialize)]
+pub struct Case {
+    pub day: String,
+    pub part: u8,
+    pub input: Option<String>,
+    pub expected: Expected,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Integer(i64),
+    Text(String),
+    List(Vec<i64>),
+}
+
+impl From<&Expected> for Value {
+    fn from(expected: &Expected) -> Value {
+        match expected {
+            Expected::Integer(i) => Value::Integer(*i),
+            Expected::Text(text) => Value::Text(text.clone()),
+            Expected::List(list) => Value::List(list.clone()),
+        }
+    }
+}
+
+pub enum Verdict {
+    Pass,
+    Fail { expected: Value, actual: Value },
+    Error(String),
+}
+
+pub struct Check {
+    pub case: Case,
+    pub verdict: Verdict,
+}
+
+pub fn load(filename: &str) -> Result<Vec<Case>, Box<dyn Error>> {
+    let text = fs::read_to_string(filename)
+        .map_err(|e| common::Error::io(filename, e))?;
+
+    let mut cases = parse(&text).map_err(|e| format!("{}: {}", filename, e))?;
+
+    let dir = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+    for case in &mut cases {
+        case.dir = dir.to_path_buf();
+    }
+
+    Ok(cases)
+}
+
+fn parse(text: &str) -> Result<Vec<Case>, Box<dyn Error>> {
+    let answers: Answers = toml::from_str(text)?;
+    Ok(answers.answer)
+}
+
+/// Runs every case through its puzzle and compares the answer to the
+/// expected value.
+pub fn run(registry: &Registry, cases: Vec<Case>, diagnostics: &mut Diagnostics) -> Vec<Check> {
+    cases.into_iter()
+        .map(|case| {
+            let verdict = match check(registry, &case, diagnostics) {
+                Ok(verdict) => verdict,
+                Err(err) => Verdict::Error(err.to_string()),
+            };
+            Check { case, verdict }
+        })
+        .collect()
+}
+
+fn check(registry: &Registry, case: &Case, diagnostics: &mut Diagnostics) -> Result<Verdict, Box<dyn Error>> {
+    let puzzle = match registry.find(&case.day) {
+        Some(puzzle) => puzzle,
+        None => return Err("Unknown puzzle name".into()),
+    };
+
+    let part = match case.part {
+        1 => Part::One,
+        2 => Part::Two,
+        _ => return Err("'Part' must be 1 or 2".into()),
+    };
+
+    let input = match &case.input {
+        Some(input) if input == "-" => Input::Stdin,
+        Some(input) => Input::Path(case.dir.join(input)),
+        None => puzzle.input()?,
+    };
+    let data = puzzle.parse(&input, diagnostics)?;
+    let answer = puzzle.solve(part, data.as_ref())?;
+
+    let expected = Value::from(&case.expected);
+    if answer.value == expected {
+        Ok(Verdict::Pass)
+    } else {
+        Ok(Verdict::Fail { expected, actual: answer.value })
+    }
+}
+
+pub fn table(checks: &[Check]) -> String {
+    let rows: Vec<Vec<String>> = checks.iter()
+        .map(|c| vec![
+            c.case.day.clone(),
+            c.case.part.to_string(),
+            c.case.input.clone().unwrap_or_default(),
+            match &c.verdict {
+                Verdict::Pass => String::from("pass"),
+                Verdict::Fail { .. } => String::from("FAIL"),
+                Verdict::Error(_) => String::from("ERROR"),
+            },
+        ])
+        .collect();
+
+    render::table(&["Day", "Part", "Input", "Result"], &rows)
+}
+
+/// Describes every case that did not pass, with the expected and actual
+/// answers for wrong answers.
+pub fn failures(checks: &[Check]) -> Vec<String> {
+    checks.iter()
+        .filter_map(|c| {
+            let name = format!("{} part {}", c.case.day, c.case.part);
+            match &c.verdict {
+                Verdict::Pass => None,
+                Verdict::Fail { expected, actual } => Some(format!(
+                    "{}:\n  expected: {}\n  actual:   {}",
+                    name, render::value(expected), render::value(actual))),
+                Verdict::Error(err) => Some(format!("{}: {}", name, err)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_should_read_cases() {
+        // arrange
+        let text = r#"
+            [[answer]]
+            day = "day1"
+            part = 1
+            input = "day1/data.txt"
+            expected = 866436
+
+            [[answer]]
+            day = "day3"
+            part = 2
+            expected = "abc"
+        "#;
+
+        // act
+        let cases = parse(text).unwrap();
+
+        // assert
+        assert_eq!(2, cases.len());
+        assert_eq!("day1", cases[0].day);
+        assert_eq!(Some(String::from("day1/data.txt")), cases[0].input);
+        assert!(matches!(cases[0].expected, Expected::Integer(866436)));
+        assert_eq!(None, cases[1].input);
+        assert!(matches!(&cases[1].expected, Expected::Text(t) if t == "abc"));
+    }
+
+    #[test]
+    fn failures_should_show_diff() {
+        // arrange
+        let checks = vec![
+            Check {
+                case: Case { day: String::from("day2"), part: 1, input: None, expected: Expected::Integer(1), dir: PathBuf::new() },
+                verdict: Verdict::Fail { expected: Value::Integer(1), actual: Value::Integer(2) },
+            },
+            Check {
+                case: Case { day: String::from("day2"), part: 2, input: None, expected: Expected::Integer(1), dir: PathBuf::new() },
+                verdict: Verdict::Pass,
+            },
+        ];
+
+        // act
+        let failures = failures(&checks);
+
+        // assert
+        assert_eq!(vec!["day2 part 1:\n  expected: 1\n  actual:   2"], failures);
+    }
+}
@@ -0,0 +1,91 @@
+/// A single value produced by a puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Text(String),
+    List(Vec<i64>),
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Value {
+        Value::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Value {
+        Value::Integer(i64::from(value))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::Text(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::Text(String::from(value))
+    }
+}
+
+impl From<Vec<i64>> for Value {
+    fn from(value: Vec<i64>) -> Value {
+        Value::List(value)
+    }
+}
+
+impl From<Vec<i32>> for Value {
+    fn from(value: Vec<i32>) -> Value {
+        Value::List(value.into_iter().map(i64::from).collect())
+    }
+}
+
+/// The answer to a puzzle, along with any labelled values that were used to
+/// reach it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answer {
+    pub value: Value,
+    pub details: Vec<(String, Value)>,
+}
+
+impl Answer {
+    pub fn new<V: Into<Value>>(value: V) -> Answer {
+        Answer {
+            value: value.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with<V: Into<Value>>(mut self, label: &str, value: V) -> Answer {
+        self.details.push((String::from(label), value.into()));
+        self
+    }
+
+    pub fn detail(&self, label: &str) -> Option<&Value> {
+        self.details.iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_should_add_details_in_order() {
+        // arrange
+        // act
+        let answer = Answer::new(6)
+            .with("Numbers", vec![2, 3])
+            .with("Sum", 5);
+
+        // assert
+        assert_eq!(Value::Integer(6), answer.value);
+        assert_eq!("Numbers", answer.details[0].0);
+        assert_eq!(Some(&Value::Integer(5)), answer.detail("Sum"));
+        assert_eq!(None, answer.detail("Product"));
+    }
+}
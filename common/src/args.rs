@@ -0,0 +1,262 @@
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// How a [`Param`] takes its values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Takes a single value. Given more than once, the last value wins.
+    Value,
+    /// Takes any number of values. Trailing positional arguments all go here.
+    Multiple,
+    /// Takes no value, e.g. the `--part1` preset.
+    Flag,
+}
+
+/// A command line parameter of a puzzle, given as `--{name} {VALUE}` or
+/// positionally in the order the parameters are declared.
+#[derive(Clone, Copy, Debug)]
+pub struct Param {
+    pub name: &'static str,
+    /// Name of the parameter in error messages, e.g. `Sum`.
+    pub label: &'static str,
+    /// Placeholder for the value in help text, e.g. `FILE`.
+    pub value: &'static str,
+    pub help: &'static str,
+    pub kind: Kind,
+}
+
+/// Arguments parsed against a puzzle's parameters.
+#[derive(Debug)]
+pub struct Args {
+    params: &'static [Param],
+    values: Vec<(&'static str, String)>,
+    help: bool,
+}
+
+impl Args {
+    pub fn parse(params: &'static [Param], args: Vec<String>) -> Result<Args, Error> {
+        let mut values: Vec<(&'static str, String)> = Vec::new();
+        let mut help = false;
+
+        let mut positional = params.iter().filter(|p| p.kind != Kind::Flag);
+        let mut current: Option<&'static Param> = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                help = true;
+                continue;
+            }
+
+            if let Some(flag) = arg.strip_prefix("--") {
+                let (name, inline) = match flag.find('=') {
+                    Some(i) => (&flag[..i], Some(String::from(&flag[i + 1..]))),
+                    None => (flag, None),
+                };
+
+                let param = match params.iter().find(|p| p.name == name) {
+                    Some(param) => param,
+                    None => return Err(Error::UnknownArgument(arg.clone())),
+                };
+
+                let value = match (param.kind, inline) {
+                    (Kind::Flag, None) => String::new(),
+                    (Kind::Flag, Some(_)) => return Err(Error::invalid_argument(param.label, "does not take a value")),
+                    (_, Some(value)) => value,
+                    (_, None) => match args.next() {
+                        Some(value) => value,
+                        None => return Err(Error::MissingArgument(param.label)),
+                    },
+                };
+
+                values.push((param.name, value));
+                continue;
+            }
+
+            // Positional values fill each parameter in turn, except that a
+            // parameter taking multiple values keeps every value after it
+            let param = match current {
+                Some(param) if param.kind == Kind::Multiple => param,
+                _ => match positional.next() {
+                    Some(param) => param,
+                    None => return Err(Error::UnknownArgument(arg)),
+                },
+            };
+
+            current = Some(param);
+            values.push((param.name, arg));
+        }
+
+        Ok(Args { params, values, help })
+    }
+
+    /// Whether `--help` was given.
+    pub fn help(&self) -> bool {
+        self.help
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.values.iter().any(|(n, _)| *n == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.iter().rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn all(&self, name: &str) -> Vec<&str> {
+        self.values.iter()
+            .filter(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    pub fn require(&self, name: &str) -> Result<&str, Error> {
+        match self.get(name) {
+            Some(value) => Ok(value),
+            None => Err(Error::MissingArgument(self.label(name))),
+        }
+    }
+
+    pub fn integer<T>(&self, name: &str) -> Result<T, Error>
+    where T: FromStr<Err = ParseIntError> {
+        let value = self.require(name)?;
+        value.parse().map_err(|e| Error::bad_integer(self.label(name), value, e))
+    }
+
+    fn label(&self, name: &str) -> &'static str {
+        match self.params.iter().find(|p| p.name == name) {
+            Some(param) => param.label,
+            None => "Unknown",
+        }
+    }
+}
+
+/// Generates the `--help` text for a puzzle from its parameters.
+pub fn help(name: &str, about: &str, params: &[Param]) -> String {
+    let mut usage = vec![format!("Usage: advent run {}", name)];
+    let mut options: Vec<(String, &str)> = Vec::new();
+
+    for param in params {
+        let option = match param.kind {
+            Kind::Flag => format!("--{}", param.name),
+            Kind::Value => format!("--{} <{}>", param.name, param.value),
+            Kind::Multiple => format!("--{} <{}>...", param.name, param.value),
+        };
+
+        if param.kind != Kind::Flag {
+            usage.push(format!("[{}]", option));
+        }
+        options.push((option, param.help));
+    }
+    options.push((String::from("--help"), "Print this help"));
+
+    let width = options.iter().map(|(o, _)| o.len()).max().unwrap_or(0);
+
+    let mut text = format!("{} - {}\n\n{}\n\nOptions:", name, about, usage.join(" "));
+    for (option, help) in options {
+        text.push_str(&format!("\n  {:width$}  {}", option, help, width = width));
+    }
+
+    text.push_str("\n\nValues may also be given in order without their --name.");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PARAMS: [Param; 4] = [
+        Param { name: "input", label: "Input", value: "FILE", help: "Input file", kind: Kind::Value },
+        Param { name: "sum", label: "Sum", value: "N", help: "Target sum", kind: Kind::Value },
+        Param { name: "slope", label: "Slope", value: "X,Y", help: "Slope", kind: Kind::Multiple },
+        Param { name: "part1", label: "Part 1", value: "", help: "Preset", kind: Kind::Flag },
+    ];
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| String::from(*a)).collect()
+    }
+
+    #[test]
+    fn parse_should_read_named_values() {
+        // arrange
+        let args = strings(&["--sum", "2020", "--input=data.txt", "--part1"]);
+
+        // act
+        let args = Args::parse(&PARAMS, args).unwrap();
+
+        // assert
+        assert_eq!(Some("data.txt"), args.get("input"));
+        assert_eq!(2020, args.integer::<i32>("sum").unwrap());
+        assert!(args.flag("part1"));
+        assert!(!args.help());
+    }
+
+    #[test]
+    fn parse_should_fill_positional_values_in_order() {
+        // arrange
+        let args = strings(&["data.txt", "2020", "1,1", "3,1"]);
+
+        // act
+        let args = Args::parse(&PARAMS, args).unwrap();
+
+        // assert
+        assert_eq!(Some("data.txt"), args.get("input"));
+        assert_eq!(Some("2020"), args.get("sum"));
+        assert_eq!(vec!["1,1", "3,1"], args.all("slope"));
+    }
+
+    #[test]
+    fn parse_should_reject_unknown_flags() {
+        // arrange
+        let args = strings(&["--count", "3"]);
+
+        // act
+        let result = Args::parse(&PARAMS, args);
+
+        // assert
+        assert_eq!("Unknown argument '--count'", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn parse_should_require_flag_values() {
+        // arrange
+        let args = strings(&["--sum"]);
+
+        // act
+        let result = Args::parse(&PARAMS, args);
+
+        // assert
+        assert_eq!("'Sum' parameter not supplied", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn integer_should_name_parameter() {
+        // arrange
+        let args = Args::parse(&PARAMS, strings(&["--sum", "abc"])).unwrap();
+
+        // act
+        let result = args.integer::<i32>("sum");
+
+        // assert
+        assert_eq!("'Sum' parameter must be an integer, got 'abc'", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn help_should_list_parameters() {
+        // arrange
+        // act
+        let text = help("day9", "Test puzzle", &PARAMS);
+
+        // assert
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!("day9 - Test puzzle", lines[0]);
+        assert_eq!("Usage: advent run day9 [--input <FILE>] [--sum <N>] [--slope <X,Y>...]", lines[2]);
+        assert_eq!("  --input <FILE>    Input file", lines[5]);
+        assert_eq!("  --part1           Preset", lines[8]);
+        assert_eq!("  --help            Print this help", lines[9]);
+    }
+}
@@ -1,22 +1,26 @@
+use crate::Error;
+
+/// Command line of the `advent` binary, split into the command, such as `run`
+/// or `list`, and the arguments that follow it.
 pub struct Config {
-    pub puzzle_name: String,
+    pub command: String,
     pub args: Vec<String>,
 }
 
 impl Config {
-    pub fn new<T>(mut args: T) -> Result<Config, &'static str>
+    pub fn new<T>(mut args: T) -> Result<Config, Error>
     where T : Iterator<Item = String> {
         args.next(); // skip program name
 
-        let puzzle_name = match args.next() {
+        let command = match args.next() {
             Some(arg) => arg,
-            None => return Err("Must supply a puzzle name"),
+            None => return Err(Error::MissingArgument("Command")),
         };
 
         let args: Vec<String> = args.collect();
 
         Ok(Config {
-            puzzle_name,
+            command,
             args,
         })
     }
@@ -27,7 +31,7 @@ mod tests {
     use super::*;
 
     #[test]
-    #[should_panic(expected = "Must supply a puzzle name")]
+    #[should_panic(expected = "MissingArgument(\"Command\")")]
     fn new_should_return_err_for_missing_args() {
         // arrange
         let args: Vec<String> = vec![];
@@ -39,7 +43,7 @@ mod tests {
     }
 
     #[test]
-    fn new_should_get_puzzle_name() {
+    fn new_should_get_command() {
         // arrange
         let args: Vec<String> = vec![String::from("_"), String::from("puzzle")];
         let args = args.into_iter();
@@ -48,7 +52,7 @@ mod tests {
         let config = Config::new(args).unwrap();
 
         // assert
-        assert_eq!("puzzle", config.puzzle_name);
+        assert_eq!("puzzle", config.command);
     }
 
     #[test]
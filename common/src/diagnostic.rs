@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::Error;
+
+/// Location and description of a line of input that could not be parsed.
+/// `line` and `column` are 1-based, with 0 meaning the location is not known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub path: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Renders the message followed by the offending line with a caret under
+    /// the column at fault.
+    ///
+    /// ```text
+    /// day2/data.txt:57:3: expected 'min-max letter: password'
+    ///    |
+    /// 57 | 3 a: abc
+    ///    |   ^
+    /// ```
+    pub fn render(&self) -> String {
+        let mut text = self.to_string();
+
+        if self.column == 0 {
+            return text;
+        }
+
+        let number = if self.line > 0 { self.line.to_string() } else { String::new() };
+        let gutter = " ".repeat(number.len());
+        let offset = " ".repeat(self.column - 1);
+
+        text.push_str(&format!("\n{} |", gutter));
+        text.push_str(&format!("\n{} | {}", number, self.text));
+        text.push_str(&format!("\n{} | {}^", gutter, offset));
+
+        text
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = Vec::new();
+        if let Some(path) = &self.path {
+            location.push(path.clone());
+        }
+        if self.line > 0 {
+            location.push(self.line.to_string());
+        }
+        if self.line > 0 && self.column > 0 {
+            location.push(self.column.to_string());
+        }
+
+        if location.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", location.join(":"), self.message)
+        }
+    }
+}
+
+/// How readers treat lines that fail to parse.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Strictness {
+    /// Stop at the first bad line.
+    #[default]
+    Strict,
+    /// Skip bad lines, keeping them as warnings.
+    Lenient,
+}
+
+/// Collects the parse failures met while reading an input.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    strictness: Strictness,
+    warnings: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(strictness: Strictness) -> Diagnostics {
+        Diagnostics { strictness, warnings: Vec::new() }
+    }
+
+    /// Reports a failure from a reader. Parse errors are kept as warnings when
+    /// lenient, in which case the reader should skip the line and carry on.
+    /// Anything else is handed back to stop the read.
+    pub fn report(&mut self, error: Error) -> Result<(), Error> {
+        match (self.strictness, error) {
+            (Strictness::Lenient, Error::Parse(diagnostic)) => {
+                self.warnings.push(diagnostic);
+                Ok(())
+            },
+            (_, error) => Err(error),
+        }
+    }
+
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_should_underline_column() {
+        // arrange
+        let diagnostic = Diagnostic {
+            path: Some(String::from("day2/data.txt")),
+            line: 57,
+            column: 3,
+            text: String::from("3 a: abc"),
+            message: String::from("expected 'min-max letter: password'"),
+        };
+
+        // act
+        let text = diagnostic.render();
+
+        // assert
+        let expected = "day2/data.txt:57:3: expected 'min-max letter: password'\n   |\n57 | 3 a: abc\n   |   ^";
+        assert_eq!(expected, text);
+    }
+
+    #[test]
+    fn report_should_fail_when_strict() {
+        // arrange
+        let mut diagnostics = Diagnostics::new(Strictness::Strict);
+
+        // act
+        let result = diagnostics.report(Error::parse("x", 1, "bad"));
+
+        // assert
+        assert!(result.is_err());
+        assert!(diagnostics.warnings().is_empty());
+    }
+
+    #[test]
+    fn report_should_keep_warning_when_lenient() {
+        // arrange
+        let mut diagnostics = Diagnostics::new(Strictness::Lenient);
+
+        // act
+        let result = diagnostics.report(Error::parse("x", 1, "bad").on_line(4));
+
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(4, diagnostics.warnings()[0].line);
+    }
+
+    #[test]
+    fn report_should_fail_on_io_errors_when_lenient() {
+        // arrange
+        let mut diagnostics = Diagnostics::new(Strictness::Lenient);
+        let source = std::io::Error::other("broken");
+
+        // act
+        let result = diagnostics.report(Error::io("_", source));
+
+        // assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+use std::path::PathBuf;
+
+use crate::Diagnostic;
+
+/// Errors raised while configuring, reading or solving a puzzle.
+#[derive(Debug)]
+pub enum Error {
+    /// A required command line argument was not supplied.
+    MissingArgument(&'static str),
+    /// A command line argument that should be an integer could not be parsed.
+    BadInteger {
+        name: &'static str,
+        value: String,
+        source: ParseIntError,
+    },
+    /// A command line argument does not match any parameter.
+    UnknownArgument(String),
+    /// A command line argument was supplied but is not usable.
+    InvalidArgument {
+        name: &'static str,
+        message: String,
+    },
+    /// An input file could not be opened or read.
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    /// A preset input could not be found in any of the searched locations.
+    InputNotFound {
+        name: String,
+        searched: Vec<PathBuf>,
+    },
+    /// A line of input did not match the expected format.
+    Parse(Diagnostic),
+    /// The input was read successfully but has no answer.
+    NoSolution(&'static str),
+    /// A calculation does not fit in the integer type it is done in.
+    Overflow {
+        what: &'static str,
+        type_name: &'static str,
+    },
+}
+
+impl Error {
+    pub fn bad_integer(name: &'static str, value: &str, source: ParseIntError) -> Error {
+        Error::BadInteger { name, value: String::from(value), source }
+    }
+
+    pub fn invalid_argument(name: &'static str, message: &str) -> Error {
+        Error::InvalidArgument { name, message: String::from(message) }
+    }
+
+    pub fn io(path: &str, source: io::Error) -> Error {
+        Error::Io { path: String::from(path), source }
+    }
+
+    /// Creates a parse error for `text`, whose location in the input is filled
+    /// in later with [`Error::on_line`] and [`Error::in_file`].
+    pub fn parse(text: &str, column: usize, message: &str) -> Error {
+        Error::Parse(Diagnostic {
+            path: None,
+            line: 0,
+            column,
+            text: String::from(text),
+            message: String::from(message),
+        })
+    }
+
+    /// Sets the line number of a parse error. Other errors are unchanged.
+    pub fn on_line(mut self, number: usize) -> Error {
+        if let Error::Parse(diagnostic) = &mut self {
+            diagnostic.line = number;
+        }
+        self
+    }
+
+    /// Sets the file of a parse error. Other errors are unchanged.
+    pub fn in_file(mut self, filename: &str) -> Error {
+        if let Error::Parse(diagnostic) = &mut self {
+            diagnostic.path = Some(String::from(filename));
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingArgument(name) => write!(f, "'{}' parameter not supplied", name),
+            Error::BadInteger { name, value, .. } =>
+                write!(f, "'{}' parameter must be an integer, got '{}'", name, value),
+            Error::UnknownArgument(arg) => write!(f, "Unknown argument '{}'", arg),
+            Error::InvalidArgument { name, message } => write!(f, "'{}' parameter {}", name, message),
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::InputNotFound { name, searched } => {
+                write!(f, "Could not find input '{}', searched:", name)?;
+                for path in searched {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                write!(f, "\nSet {} to the directory holding each day's input", crate::resolver::INPUT_DIR_VAR)
+            },
+            Error::Parse(diagnostic) => write!(f, "{}", diagnostic),
+            Error::NoSolution(message) => write!(f, "{}", message),
+            Error::Overflow { what, type_name } => write!(f, "{} overflows {}", what, type_name),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::BadInteger { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn display_should_show_parse_location() {
+        // arrange
+        let error = Error::parse("3 a", 3, "expected 'min-max letter: password'")
+            .on_line(57)
+            .in_file("day2/data.txt");
+
+        // act
+        let message = error.to_string();
+
+        // assert
+        assert_eq!("day2/data.txt:57:3: expected 'min-max letter: password'", message);
+    }
+
+    #[test]
+    fn display_should_omit_unknown_location() {
+        // arrange
+        let error = Error::parse("#x", 2, "invalid character 'x'");
+
+        // act
+        let message = error.to_string();
+
+        // assert
+        assert_eq!("invalid character 'x'", message);
+    }
+
+    #[test]
+    fn bad_integer_should_have_source() {
+        // arrange
+        let source = "abc".parse::<i32>().unwrap_err();
+
+        // act
+        let error = Error::bad_integer("Sum", "abc", source);
+
+        // assert
+        assert_eq!("'Sum' parameter must be an integer, got 'abc'", error.to_string());
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn io_should_include_path() {
+        // arrange
+        let source = io::Error::new(io::ErrorKind::NotFound, "not found");
+
+        // act
+        let error = Error::io("day1/data.txt", source);
+
+        // assert
+        assert_eq!("day1/data.txt: not found", error.to_string());
+        assert!(error.source().is_some());
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use flate2::bufread::MultiGzDecoder;
+
+use crate::Error;
+
+/// First bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Where a puzzle reads its input from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    Path(PathBuf),
+    Stdin,
+    Text(String),
+}
+
+impl Input {
+    /// Interprets a command line argument, where `-` means standard input and
+    /// anything else is a file path.
+    pub fn from_arg(arg: &str) -> Input {
+        match arg {
+            "-" => Input::Stdin,
+            _ => Input::Path(PathBuf::from(arg)),
+        }
+    }
+
+    pub fn text(text: &str) -> Input {
+        Input::Text(String::from(text))
+    }
+
+    /// Name used to refer to the input in error messages.
+    pub fn name(&self) -> String {
+        match self {
+            Input::Path(path) => path.display().to_string(),
+            Input::Stdin => String::from("<stdin>"),
+            Input::Text(_) => String::from("<text>"),
+        }
+    }
+
+    pub fn lines(&self) -> Result<Box<dyn Iterator<Item = Result<String, Error>>>, Error> {
+        let name = self.name();
+        let lines = self.reader()?.lines();
+
+        Ok(Box::new(lines.map(move |line| line.map_err(|e| Error::io(&name, e)))))
+    }
+
+    /// Opens the input for reading a line at a time into a buffer the caller
+    /// owns, rather than allocating a `String` per line as [`Input::lines`]
+    /// does. Gzip compressed input is decompressed as it is read.
+    pub fn reader(&self) -> Result<Box<dyn BufRead>, Error> {
+        let reader: Box<dyn BufRead> = match self {
+            Input::Path(path) => {
+                let file = File::open(path).map_err(|e| Error::io(&self.name(), e))?;
+                Box::new(BufReader::new(file))
+            },
+            Input::Stdin => Box::new(io::stdin().lock()),
+            Input::Text(text) => Box::new(io::Cursor::new(text.clone().into_bytes())),
+        };
+
+        decompress(reader).map_err(|e| Error::io(&self.name(), e))
+    }
+}
+
+/// Unwraps gzip compressed input, recognised by its first bytes rather than
+/// the file name so compressed standard input works too.
+fn decompress(mut reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        return Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))));
+    }
+
+    Ok(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_arg_should_treat_dash_as_stdin() {
+        // arrange
+        // act
+        let input = Input::from_arg("-");
+
+        // assert
+        assert_eq!(Input::Stdin, input);
+        assert_eq!(Input::Path(PathBuf::from("day1/data.txt")), Input::from_arg("day1/data.txt"));
+    }
+
+    #[test]
+    fn lines_should_split_text() {
+        // arrange
+        let input = Input::text("1721\n979\n");
+
+        // act
+        let lines: Vec<String> = input.lines().unwrap().map(|l| l.unwrap()).collect();
+
+        // assert
+        assert_eq!(vec!["1721", "979"], lines);
+    }
+
+    #[test]
+    fn reader_should_read_text() {
+        // arrange
+        let input = Input::text("1721\n979\n");
+        let mut line = String::new();
+
+        // act
+        let mut reader = input.reader().unwrap();
+        reader.read_line(&mut line).unwrap();
+
+        // assert
+        assert_eq!("1721\n", line);
+        assert_eq!(Some("979"), reader.lines().next().map(|l| l.unwrap()).as_deref());
+    }
+
+    #[test]
+    fn lines_should_decompress_gzip() {
+        // arrange
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"1721\n979\n").unwrap();
+        let path = std::env::temp_dir().join(format!("common-input-{}.gz", std::process::id()));
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        // act
+        let lines: Vec<String> = Input::Path(path.clone()).lines().unwrap().map(|l| l.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        // assert
+        assert_eq!(vec!["1721", "979"], lines);
+    }
+
+    #[test]
+    fn lines_should_name_missing_file() {
+        // arrange
+        let input = Input::from_arg("missing/data.txt");
+
+        // act
+        let result = input.lines();
+
+        // assert
+        match result {
+            Err(err) => assert!(err.to_string().starts_with("missing/data.txt: ")),
+            Ok(_) => panic!("expected an I/O error"),
+        }
+    }
+}
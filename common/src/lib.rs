@@ -1,13 +1,21 @@
-use std::fs::File;
-use std::io::{self, BufRead, Lines, BufReader};
-use std::path::Path;
-
+mod answer;
+mod args;
 mod config;
+mod diagnostic;
+mod error;
+mod input;
+mod puzzle;
+mod records;
+mod resolver;
+mod table;
 
+pub use answer::{Answer, Value};
+pub use args::{help, Args, Kind, Param};
 pub use config::Config;
-
-pub fn read_lines<P>(filename: &P) -> io::Result<Lines<BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
+pub use diagnostic::{Diagnostic, Diagnostics, Strictness};
+pub use error::Error;
+pub use input::Input;
+pub use puzzle::{Part, Puzzle, Registry, Solver};
+pub use records::{read_records, Format, Record};
+pub use resolver::{resolve, CONFIG_FILE, INPUT_DIR_VAR};
+pub use table::table;
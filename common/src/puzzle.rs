@@ -0,0 +1,213 @@
+use std::any::Any;
+
+use crate::{Answer, Args, Diagnostics, Error, Input, Param};
+
+/// Which half of a day's puzzle to solve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+/// A single day's puzzle.
+///
+/// Days implement this trait and register themselves with a [`Registry`] so
+/// the `advent` binary can look them up by name.
+pub trait Puzzle: Sync {
+    /// Parsed puzzle input shared by both parts.
+    type Data: 'static;
+
+    /// Name used to select the puzzle from the command line, e.g. `day1`.
+    fn name(&self) -> &'static str;
+
+    /// One line description shown by `advent list` and in `--help`.
+    fn about(&self) -> &'static str;
+
+    /// Parameters accepted by [`Puzzle::run`], used to parse its arguments and
+    /// generate its `--help`.
+    fn params(&self) -> &'static [Param];
+
+    /// Locates the input used by the `--part1` and `--part2` presets.
+    fn input(&self) -> Result<Input, Error>;
+
+    /// Reads the input, reporting lines that fail to parse to `diagnostics`.
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Self::Data, Error>;
+
+    fn part1(&self, data: &Self::Data) -> Result<Answer, Error>;
+
+    fn part2(&self, data: &Self::Data) -> Result<Answer, Error>;
+
+    /// Runs the puzzle with its own command line arguments.
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error>;
+}
+
+/// Object safe view of a [`Puzzle`], used to store days of differing input
+/// types in the same [`Registry`].
+pub trait Solver: Sync {
+    fn name(&self) -> &'static str;
+
+    fn about(&self) -> &'static str;
+
+    fn params(&self) -> &'static [Param];
+
+    fn input(&self) -> Result<Input, Error>;
+
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Box<dyn Any>, Error>;
+
+    fn solve(&self, part: Part, data: &dyn Any) -> Result<Answer, Error>;
+
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error>;
+}
+
+impl<P: Puzzle> Solver for P {
+    fn name(&self) -> &'static str {
+        Puzzle::name(self)
+    }
+
+    fn about(&self) -> &'static str {
+        Puzzle::about(self)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        Puzzle::params(self)
+    }
+
+    fn input(&self) -> Result<Input, Error> {
+        Puzzle::input(self)
+    }
+
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Box<dyn Any>, Error> {
+        let data = Puzzle::parse(self, input, diagnostics)?;
+        Ok(Box::new(data))
+    }
+
+    fn solve(&self, part: Part, data: &dyn Any) -> Result<Answer, Error> {
+        let data = match data.downcast_ref::<P::Data>() {
+            Some(data) => data,
+            None => return Err(Error::invalid_argument("Data", "was not parsed by this puzzle")),
+        };
+
+        match part {
+            Part::One => self.part1(data),
+            Part::Two => self.part2(data),
+        }
+    }
+
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+        Puzzle::run(self, args, diagnostics)
+    }
+}
+
+/// Collection of every puzzle known to the `advent` binary.
+#[derive(Default)]
+pub struct Registry {
+    puzzles: Vec<&'static dyn Solver>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry { puzzles: Vec::new() }
+    }
+
+    pub fn register(&mut self, puzzle: &'static dyn Solver) {
+        self.puzzles.push(puzzle);
+    }
+
+    pub fn find(&self, name: &str) -> Option<&'static dyn Solver> {
+        self.puzzles.iter().find(|p| p.name() == name).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'static dyn Solver> + '_ {
+        self.puzzles.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Doubler;
+
+    impl Puzzle for Doubler {
+        type Data = i32;
+
+        fn name(&self) -> &'static str {
+            "doubler"
+        }
+
+        fn about(&self) -> &'static str {
+            "Doubles a number"
+        }
+
+        fn params(&self) -> &'static [Param] {
+            &[]
+        }
+
+        fn input(&self) -> Result<Input, Error> {
+            Ok(Input::text("2"))
+        }
+
+        fn parse(&self, input: &Input, _diagnostics: &mut Diagnostics) -> Result<i32, Error> {
+            let text = match input {
+                Input::Text(text) => text,
+                _ => return Err(Error::NoSolution("Only text input is supported")),
+            };
+            text.parse().map_err(|e| Error::bad_integer("Input", text, e))
+        }
+
+        fn part1(&self, data: &i32) -> Result<Answer, Error> {
+            Ok(Answer::new(data * 2))
+        }
+
+        fn part2(&self, _data: &i32) -> Result<Answer, Error> {
+            Err(Error::NoSolution("Not implemented"))
+        }
+
+        fn run(&self, _args: &Args, _diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+            Ok(Answer::new(0))
+        }
+    }
+
+    static DOUBLER: Doubler = Doubler;
+
+    #[test]
+    fn find_should_return_registered_puzzle() {
+        // arrange
+        let mut registry = Registry::new();
+        registry.register(&DOUBLER);
+
+        // act
+        let puzzle = registry.find("doubler");
+
+        // assert
+        assert!(puzzle.is_some());
+        assert!(registry.find("missing").is_none());
+    }
+
+    #[test]
+    fn solve_should_dispatch_parts() {
+        // arrange
+        let mut registry = Registry::new();
+        registry.register(&DOUBLER);
+        let puzzle = registry.find("doubler").unwrap();
+
+        // act
+        let data = puzzle.parse(&Input::text("2"), &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(Answer::new(4), puzzle.solve(Part::One, data.as_ref()).unwrap());
+        assert!(puzzle.solve(Part::Two, data.as_ref()).is_err());
+    }
+
+    #[test]
+    fn solve_should_reject_foreign_data() {
+        // arrange
+        let data: Box<dyn Any> = Box::new(String::from("2"));
+
+        // act
+        let result = Solver::solve(&DOUBLER, Part::One, data.as_ref());
+
+        // assert
+        assert!(result.is_err());
+    }
+}
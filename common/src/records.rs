@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::iter;
+use std::path::Path;
+
+use crate::{Error, Input};
+
+/// How the lines of an input hold their fields.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Plain lines, read by the puzzle's own parser.
+    Text,
+    /// Comma separated values.
+    Csv,
+    /// Tab separated values.
+    Tsv,
+    /// A JSON object on each line.
+    JsonLines,
+}
+
+impl Format {
+    /// Interprets a command line argument naming a format.
+    pub fn from_arg(name: &'static str, arg: &str) -> Result<Format, Error> {
+        match arg {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "jsonl" => Ok(Format::JsonLines),
+            _ => Err(Error::invalid_argument(name, &format!("must be text, csv, tsv or jsonl, got '{}'", arg))),
+        }
+    }
+
+    /// Guesses the format from the input's file extension, looking past a
+    /// `.gz` suffix. Anything unrecognised is read as text.
+    pub fn detect(input: &Input) -> Format {
+        let path = match input {
+            Input::Path(path) => path,
+            _ => return Format::Text,
+        };
+
+        let path = match path.extension() {
+            Some(ext) if ext == "gz" => Path::new(path.file_stem().unwrap_or_default()),
+            _ => path.as_path(),
+        };
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Format::Csv,
+            Some("tsv") | Some("tab") => Format::Tsv,
+            Some("jsonl") | Some("ndjson") => Format::JsonLines,
+            _ => Format::Text,
+        }
+    }
+}
+
+/// The fields picked from one record of an input, in the order their columns
+/// were asked for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// 1-based line the record starts on.
+    pub line: usize,
+    /// The record as written, shown in parse errors.
+    pub text: String,
+    pub fields: Vec<String>,
+    /// 1-based column of each field in `text`, or 1 if not known.
+    columns: Vec<usize>,
+}
+
+impl Record {
+    /// A parse error pointing at one of the record's fields.
+    pub fn error(&self, field: usize, message: &str) -> Error {
+        let column = self.columns.get(field).copied().unwrap_or(1);
+        Error::parse(&self.text, column, message).on_line(self.line)
+    }
+}
+
+type Records = Box<dyn Iterator<Item = Result<Record, Error>>>;
+
+/// Reads the records of a CSV, TSV or JSON Lines input, picking out the given
+/// columns of each. Columns are 1-based indices when every one is a number,
+/// and otherwise names from the header row or keys of each JSON object. Text
+/// input gives each line as a single field.
+///
+/// Records missing a column are parse errors, leaving the caller to decide
+/// whether to skip them. Blank lines are skipped.
+pub fn read_records(input: &Input, format: Format, columns: &[String]) -> Result<Records, Error> {
+    match format {
+        Format::Text => text(input),
+        Format::Csv => delimited(input, b',', columns),
+        Format::Tsv => delimited(input, b'\t', columns),
+        Format::JsonLines => json_lines(input, columns),
+    }
+}
+
+fn text(input: &Input) -> Result<Records, Error> {
+    let records = input.lines()?.enumerate().filter_map(|(index, line)| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(Ok(Record { line: index + 1, text: line.clone(), fields: vec![line], columns: vec![1] })),
+        Err(err) => Some(Err(err)),
+    });
+
+    Ok(Box::new(records))
+}
+
+/// Where to find each asked for column in a delimited record.
+enum Picker {
+    Index(Vec<usize>),
+    /// Names to look up in the header row, which is yet to be read.
+    Header(Vec<String>),
+    /// The header row lacked a column, so no record can be read.
+    Failed,
+}
+
+fn delimited(input: &Input, delimiter: u8, columns: &[String]) -> Result<Records, Error> {
+    let name = input.name();
+    let reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input.reader()?);
+
+    let indices: Option<Vec<usize>> = columns.iter()
+        .map(|column| column.parse::<usize>().ok().filter(|&n| n > 0).map(|n| n - 1))
+        .collect();
+    let mut picker = match indices {
+        Some(indices) => Picker::Index(indices),
+        None => Picker::Header(columns.to_vec()),
+    };
+
+    let separator = char::from(delimiter).to_string();
+    let mut rows = reader.into_records();
+    let records = iter::from_fn(move || loop {
+        let record = match rows.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(csv_error(&name, err))),
+        };
+
+        let fields: Vec<&str> = record.iter().collect();
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        let text = fields.join(&separator);
+
+        let indices = match &picker {
+            Picker::Index(indices) => indices,
+            Picker::Failed => return None,
+            Picker::Header(names) => {
+                let found: Result<Vec<usize>, Error> = names.iter()
+                    .map(|name| fields.iter().position(|f| f.trim() == name)
+                        .ok_or_else(|| Error::invalid_argument("Columns", &format!("no column named '{}' in the header", name))))
+                    .collect();
+
+                match found {
+                    Ok(indices) => picker = Picker::Index(indices),
+                    Err(err) => {
+                        picker = Picker::Failed;
+                        return Some(Err(err));
+                    },
+                }
+                continue;
+            },
+        };
+
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+
+        // Each field starts one character after the end of the one before
+        let starts: Vec<usize> = fields.iter()
+            .scan(1, |column, field| {
+                let start = *column;
+                *column += field.chars().count() + 1;
+                Some(start)
+            })
+            .collect();
+
+        let mut picked = Record { line, text, fields: Vec::new(), columns: Vec::new() };
+        for (position, &index) in indices.iter().enumerate() {
+            match fields.get(index) {
+                Some(field) => {
+                    picked.fields.push(String::from(*field));
+                    picked.columns.push(starts[index]);
+                },
+                None => {
+                    picked.columns.push(picked.text.chars().count() + 1);
+                    return Some(Err(picked.error(position, &format!("expected at least {} fields, found {}", index + 1, fields.len()))));
+                },
+            }
+        }
+
+        return Some(Ok(picked));
+    });
+
+    Ok(Box::new(records.fuse()))
+}
+
+fn csv_error(name: &str, err: csv::Error) -> Error {
+    let line = err.position().map_or(0, |p| p.line() as usize);
+    let message = err.to_string();
+
+    match err.into_kind() {
+        csv::ErrorKind::Io(err) => Error::io(name, err),
+        _ => Error::parse("", 0, &message).on_line(line),
+    }
+}
+
+fn json_lines(input: &Input, columns: &[String]) -> Result<Records, Error> {
+    let columns = columns.to_vec();
+
+    let records = input.lines()?.enumerate().filter_map(move |(index, line)| {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => return None,
+            Ok(line) => line,
+            Err(err) => return Some(Err(err)),
+        };
+
+        Some(json_record(index + 1, line, &columns))
+    });
+
+    Ok(Box::new(records))
+}
+
+fn json_record(line: usize, text: String, columns: &[String]) -> Result<Record, Error> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&text)
+        .map_err(|e| Error::parse(&text, e.column().max(1), &format!("expected a JSON object, {}", e)).on_line(line))?;
+
+    let keys = key_columns(&text);
+    let mut record = Record { line, text, fields: Vec::new(), columns: Vec::new() };
+
+    for column in columns {
+        record.columns.push(keys.get(column).copied().unwrap_or(1));
+
+        let field = match object.get(column) {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(serde_json::Value::Number(value)) => value.to_string(),
+            Some(_) => return Err(record.error(record.columns.len() - 1, &format!("'{}' must be a string or number", column))),
+            None => return Err(record.error(record.columns.len() - 1, &format!("expected a '{}' key", column))),
+        };
+        record.fields.push(field);
+    }
+
+    Ok(record)
+}
+
+/// The 1-based column of each key of the JSON object `text`, which must be
+/// valid. Only strings directly inside the object and followed by a colon are
+/// keys, so values and nested objects never match. A repeated key takes its
+/// last column, as its value does.
+fn key_columns(text: &str) -> HashMap<String, usize> {
+    let mut columns = HashMap::new();
+    let mut depth = 0;
+    let mut chars = text.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            '"' => {
+                let mut escaped = false;
+                let end = chars.by_ref()
+                    .find(|&(_, c)| {
+                        let closes = c == '"' && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        closes
+                    })
+                    .map_or(text.len(), |(end, _)| end + 1);
+
+                if depth == 1 && text[end..].trim_start().starts_with(':') {
+                    if let Ok(key) = serde_json::from_str::<String>(&text[start..end]) {
+                        columns.insert(key, text[..start].chars().count() + 1);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| String::from(*n)).collect()
+    }
+
+    fn fields(input: &str, format: Format, names: &[&str]) -> Vec<Vec<String>> {
+        read_records(&Input::text(input), format, &columns(names)).unwrap()
+            .map(|record| record.unwrap().fields)
+            .collect()
+    }
+
+    #[test]
+    fn detect_should_look_past_gzip_extension() {
+        // arrange
+        let path = |name: &str| Input::Path(PathBuf::from(name));
+
+        // act
+        // assert
+        assert_eq!(Format::Csv, Format::detect(&path("audit.csv")));
+        assert_eq!(Format::Tsv, Format::detect(&path("audit.tsv.gz")));
+        assert_eq!(Format::JsonLines, Format::detect(&path("audit.jsonl.gz")));
+        assert_eq!(Format::Text, Format::detect(&path("data.txt.gz")));
+        assert_eq!(Format::Text, Format::detect(&Input::Stdin));
+    }
+
+    #[test]
+    fn read_should_pick_csv_columns_by_header() {
+        // arrange
+        let input = "user,password,min\nann,\"a,b\",1\n\nbob,hunter2,2\n";
+
+        // act
+        let result = fields(input, Format::Csv, &["min", "password"]);
+
+        // assert
+        assert_eq!(vec![vec!["1", "a,b"], vec!["2", "hunter2"]], result);
+    }
+
+    #[test]
+    fn read_should_pick_tsv_columns_by_index() {
+        // arrange
+        let input = "1\t3\ta\tabcde\n2\t9\tc\tccccccccc\n";
+
+        // act
+        let result = fields(input, Format::Tsv, &["4", "1"]);
+
+        // assert
+        assert_eq!(vec![vec!["abcde", "1"], vec!["ccccccccc", "2"]], result);
+    }
+
+    #[test]
+    fn read_should_reject_short_records() {
+        // arrange
+        let input = Input::text("1,3,a\n");
+
+        // act
+        let result = read_records(&input, Format::Csv, &columns(&["1", "4"])).unwrap().next().unwrap();
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => {
+                assert_eq!((1, 6), (diagnostic.line, diagnostic.column));
+                assert_eq!("expected at least 4 fields, found 3", diagnostic.message);
+            },
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn read_should_name_missing_header_column() {
+        // arrange
+        let input = Input::text("min,max\n1,3\n");
+
+        // act
+        let result = read_records(&input, Format::Csv, &columns(&["password"])).unwrap().next().unwrap();
+
+        // assert
+        match result {
+            Err(err) => assert_eq!("'Columns' parameter no column named 'password' in the header", err.to_string()),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn read_should_stop_after_missing_header_column() {
+        // arrange
+        let input = Input::text("min,max\n1,3\n2,9\n");
+
+        // act
+        let results: Vec<Result<Record, Error>> = read_records(&input, Format::Csv, &columns(&["password"])).unwrap().collect();
+
+        // assert
+        assert_eq!(1, results.len());
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn read_should_pick_json_keys() {
+        // arrange
+        let input = "{\"min\": 1, \"password\": \"abcde\"}\n{\"password\": \"x\", \"min\": 2}\n";
+
+        // act
+        let result = fields(input, Format::JsonLines, &["password", "min"]);
+
+        // assert
+        assert_eq!(vec![vec!["abcde", "1"], vec!["x", "2"]], result);
+    }
+
+    #[test]
+    fn read_should_point_at_bad_json_key() {
+        // arrange
+        let input = Input::text("{\"min\": 1}\n{\"min\": [1]}\n");
+
+        // act
+        let errors: Vec<String> = read_records(&input, Format::JsonLines, &columns(&["min"])).unwrap()
+            .filter_map(|record| record.err())
+            .map(|err| err.to_string())
+            .collect();
+
+        // assert
+        assert_eq!(vec!["2:2: 'min' must be a string or number"], errors);
+    }
+
+    #[test]
+    fn read_should_point_at_key_not_earlier_value() {
+        // arrange
+        let input = Input::text("{\"note\": \"min\", \"nested\": {\"min\": 1}, \"mi\\u006e\": [1]}\n");
+
+        // act
+        let result = read_records(&input, Format::JsonLines, &columns(&["min"])).unwrap().next().unwrap();
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!((1, 39), (diagnostic.line, diagnostic.column)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Environment variable naming a directory that holds each day's input.
+pub const INPUT_DIR_VAR: &str = "ADVENT_INPUT_DIR";
+
+/// Config file searched for in the current directory and its parents. Its
+/// `input_dir` setting is relative to the directory holding the file.
+pub const CONFIG_FILE: &str = "advent.toml";
+
+/// Finds the preset input `file` of `day`, trying in order:
+///
+/// 1. `$ADVENT_INPUT_DIR/{day}/{file}`
+/// 2. `{input_dir}/{day}/{file}`, with `input_dir` read from `advent.toml`
+/// 3. `{day}/{file}` relative to the current directory
+/// 4. `{file}` in the day's own crate directory, `manifest_dir`
+pub fn resolve(day: &str, file: &str, manifest_dir: &str) -> Result<PathBuf, Error> {
+    let env_dir = env::var_os(INPUT_DIR_VAR).map(PathBuf::from);
+    let config_dir = match env::current_dir() {
+        Ok(dir) => config_input_dir(&dir)?,
+        Err(_) => None,
+    };
+
+    let searched = candidates(day, file, manifest_dir, env_dir, config_dir);
+
+    match searched.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(Error::InputNotFound { name: format!("{}/{}", day, file), searched }),
+    }
+}
+
+fn candidates(day: &str, file: &str, manifest_dir: &str, env_dir: Option<PathBuf>, config_dir: Option<PathBuf>) -> Vec<PathBuf> {
+    let relative = Path::new(day).join(file);
+
+    let mut candidates = Vec::new();
+    if let Some(dir) = env_dir {
+        candidates.push(dir.join(&relative));
+    }
+    if let Some(dir) = config_dir {
+        candidates.push(dir.join(&relative));
+    }
+    candidates.push(relative);
+    candidates.push(Path::new(manifest_dir).join(file));
+
+    candidates
+}
+
+/// Reads `input_dir` from the nearest `advent.toml` at or above `dir`.
+fn config_input_dir(dir: &Path) -> Result<Option<PathBuf>, Error> {
+    let config = match dir.ancestors().map(|d| d.join(CONFIG_FILE)).find(|p| p.is_file()) {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+
+    let name = config.display().to_string();
+    let text = fs::read_to_string(&config).map_err(|e| Error::io(&name, e))?;
+
+    let input_dir = parse_input_dir(&text).map_err(|message| Error::invalid_argument("input_dir", &format!("in {} {}", name, message)))?;
+
+    let base = config.parent().unwrap_or(dir);
+    Ok(input_dir.map(|input_dir| base.join(input_dir)))
+}
+
+fn parse_input_dir(text: &str) -> Result<Option<String>, String> {
+    let value: toml::Value = text.parse().map_err(|e: toml::de::Error| format!("could not be read: {}", e))?;
+
+    match value.get("input_dir") {
+        Some(toml::Value::String(dir)) => Ok(Some(dir.clone())),
+        Some(_) => Err(String::from("must be a string")),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_should_search_in_order() {
+        // arrange
+        let env_dir = Some(PathBuf::from("/env"));
+        let config_dir = Some(PathBuf::from("/config"));
+
+        // act
+        let candidates = candidates("day1", "data.txt", "/crate/day1", env_dir, config_dir);
+
+        // assert
+        assert_eq!(vec![
+            PathBuf::from("/env/day1/data.txt"),
+            PathBuf::from("/config/day1/data.txt"),
+            PathBuf::from("day1/data.txt"),
+            PathBuf::from("/crate/day1/data.txt"),
+        ], candidates);
+    }
+
+    #[test]
+    fn resolve_should_fall_back_to_manifest_dir() {
+        // arrange
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+
+        // act
+        let path = resolve("missing", "Cargo.toml", manifest_dir).unwrap();
+
+        // assert
+        assert_eq!(Path::new(manifest_dir).join("Cargo.toml"), path);
+    }
+
+    #[test]
+    fn resolve_should_list_searched_locations() {
+        // arrange
+        // act
+        let result = resolve("missing", "data.txt", "/crate/missing");
+
+        // assert
+        let message = result.unwrap_err().to_string();
+        assert!(message.starts_with("Could not find input 'missing/data.txt', searched:"));
+        assert!(message.contains("\n  /crate/missing/data.txt"));
+    }
+
+    #[test]
+    fn parse_input_dir_should_read_setting() {
+        // arrange
+        let text = "input_dir = \"inputs\"\n";
+
+        // act
+        let dir = parse_input_dir(text).unwrap();
+
+        // assert
+        assert_eq!(Some(String::from("inputs")), dir);
+        assert_eq!(None, parse_input_dir("").unwrap());
+        assert!(parse_input_dir("input_dir = 3").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+/// Lays out rows in left aligned columns separated by `|`, with a rule under
+/// the headers. Widths count characters, so letters made of several bytes
+/// still line up.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let cells: Vec<String> = cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        String::from(cells.join(" | ").trim_end())
+    };
+
+    let mut lines = vec![format_row(headers.to_vec())];
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    lines.push(rule.join("-+-"));
+
+    for row in rows {
+        lines.push(format_row(row.iter().map(|c| c.as_str()).collect()));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_should_align_columns() {
+        // arrange
+        let rows = vec![
+            vec![String::from("day1"), String::from("866436")],
+            vec![String::from("day10"), String::from("7")],
+        ];
+
+        // act
+        let text = table(&["Day", "Answer"], &rows);
+
+        // assert
+        assert_eq!("Day   | Answer\n------+-------\nday1  | 866436\nday10 | 7", text);
+    }
+}
@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+
+use crate::ksum::{compare_sum, exceeds};
+use crate::number::Number;
+
+/// Finds `k` entries of `numbers`, which must be sorted in ascending order,
+/// whose sum is as large as it can be without exceeding `target`. Returns the
+/// indices of the entries in ascending order.
+pub fn find<T: Number>(numbers: &[T], k: usize, target: &T) -> Option<Vec<usize>> {
+    debug_assert!(numbers.windows(2).all(|w| w[0] <= w[1]), "numbers must be sorted");
+
+    best(numbers, 0, k, target).map(|(_, indices)| indices)
+}
+
+/// Fixes the smallest entry and keeps whichever choice of the rest comes
+/// closest, ending in a two pointer sweep, O(n^(k-1)).
+fn best<T: Number>(numbers: &[T], start: usize, k: usize, target: &T) -> Option<(T, Vec<usize>)> {
+    let n = numbers.len();
+    if n < start + k {
+        return None;
+    }
+
+    match k {
+        0 => {
+            let zero = T::from_i128(0)?;
+            return if zero <= *target { Some((zero, Vec::new())) } else { None };
+        },
+        1 => {
+            let end = start + numbers[start..].partition_point(|x| x <= target);
+            return if end > start { Some((numbers[end - 1].clone(), vec![end - 1])) } else { None };
+        },
+        2 => return best_pair(numbers, start, target),
+        _ => {},
+    }
+
+    let mut found: Option<(T, Vec<usize>)> = None;
+
+    for i in start..=n - k {
+        let x = &numbers[i];
+
+        // Every later entry is at least x, so the sums only grow from here
+        if exceeds(x, k, target) {
+            break;
+        }
+
+        // A repeated value finds nothing the first copy did not
+        if i > start && numbers[i] == numbers[i - 1] {
+            continue;
+        }
+
+        let rest = match target.checked_sub(x) {
+            Some(rest) => rest,
+            None => continue,
+        };
+
+        if let Some((sum, mut indices)) = best(numbers, i + 1, k - 1, &rest) {
+            let total = match x.checked_add(&sum) {
+                Some(total) => total,
+                None => continue,
+            };
+
+            if found.as_ref().is_none_or(|(closest, _)| total > *closest) {
+                indices.insert(0, i);
+                let exact = total == *target;
+                found = Some((total, indices));
+
+                if exact {
+                    break;
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Sweeps inwards from both ends of `numbers[start..]`, keeping the largest
+/// pair sum that does not exceed `target`, O(n).
+fn best_pair<T: Number>(numbers: &[T], start: usize, target: &T) -> Option<(T, Vec<usize>)> {
+    let mut low = start;
+    let mut high = numbers.len() - 1;
+    let mut found: Option<(T, Vec<usize>)> = None;
+
+    while low < high {
+        let ordering = compare_sum(&numbers[low], &numbers[high], target);
+        if ordering == Ordering::Greater {
+            high -= 1;
+            continue;
+        }
+
+        if let Some(sum) = numbers[low].checked_add(&numbers[high]) {
+            if found.as_ref().is_none_or(|(closest, _)| sum > *closest) {
+                found = Some((sum, vec![low, high]));
+            }
+        }
+
+        if ordering == Ordering::Equal {
+            break;
+        }
+        low += 1;
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(numbers: &[i64], indices: Option<Vec<usize>>) -> Vec<i64> {
+        indices.unwrap().into_iter().map(|i| numbers[i]).collect()
+    }
+
+    #[test]
+    fn find_should_find_closest_without_exceeding() {
+        // arrange
+        let numbers = vec![1, 4, 9, 16, 25];
+
+        // act
+        // assert
+        assert_eq!(vec![4, 25], values(&numbers, find(&numbers, 2, &30)));
+        assert_eq!(vec![4, 9, 16], values(&numbers, find(&numbers, 3, &29)));
+        assert_eq!(vec![25], values(&numbers, find(&numbers, 1, &30)));
+    }
+
+    #[test]
+    fn find_should_prefer_exact_sum() {
+        // arrange
+        let numbers = vec![-10, 1, 4, 9, 16, 25];
+
+        // act
+        // assert
+        assert_eq!(vec![-10, 1, 25], values(&numbers, find(&numbers, 3, &16)));
+        assert_eq!(vec![-10, 9, 16, 25], values(&numbers, find(&numbers, 4, &40)));
+    }
+
+    #[test]
+    fn find_should_return_none_when_every_sum_exceeds() {
+        // arrange
+        let numbers: Vec<i64> = vec![10, 20, 30];
+
+        // act
+        // assert
+        assert_eq!(None, find(&numbers, 2, &29));
+        assert_eq!(None, find(&numbers, 3, &59));
+    }
+}
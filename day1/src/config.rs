@@ -1,47 +1,193 @@
+use std::num::IntErrorKind;
+use std::thread;
+
+use common::{Args, Error, Input, Kind, Param};
+
+/// What to report of the combinations that add up to the sum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// The product of the first combination found.
+    First,
+    /// Every distinct combination.
+    All,
+    /// Only how many distinct combinations there are.
+    Count,
+}
+
+/// What the chosen entries must satisfy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective {
+    /// `num_variables` entries adding up to `sum`.
+    Sum,
+    /// `num_variables` entries multiplying to `sum`.
+    Product,
+    /// `num_variables` entries adding up to as much as they can without
+    /// exceeding `sum`.
+    Closest,
+    /// A run of at least `num_variables` consecutive entries adding up to
+    /// `sum`.
+    Range,
+}
+
+/// The integer type entries are read and multiplied as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberType {
+    I64,
+    I128,
+    /// Arbitrary precision, with the `bigint` feature.
+    #[cfg(feature = "bigint")]
+    Big,
+}
+
 pub struct Config {
     pub filename: String,
-    pub sum: i32,
+    /// The target as written, read as the number type when solving.
+    pub sum: String,
     pub num_variables: usize,
+    pub objective: Objective,
+    pub mode: Mode,
+    pub number_type: NumberType,
+    /// Threads to search on, with 1 searching on the calling thread.
+    pub threads: usize,
+    /// Whether a search on several threads must give the same combination
+    /// as one on a single thread.
+    pub deterministic: bool,
 }
 
+pub static PARAMS: [Param; 11] = [
+    Param { name: "input", label: "Filename", value: "FILE", help: "Expense report, or `-` to read standard input", kind: Kind::Value },
+    Param { name: "sum", label: "Sum", value: "SUM", help: "Target for the chosen entries, a total unless --objective says otherwise", kind: Kind::Value },
+    Param { name: "count", label: "Number of variables", value: "COUNT", help: "How many entries to choose", kind: Kind::Value },
+    Param { name: "objective", label: "Objective", value: "OBJECTIVE", help: "What the entries must do: sum (default), product, closest or range", kind: Kind::Value },
+    Param { name: "type", label: "Type", value: "TYPE", help: "Integer type of the entries: i64 (default), i128 or big", kind: Kind::Value },
+    Param { name: "all", label: "All", value: "", help: "List every distinct combination instead of the first", kind: Kind::Flag },
+    Param { name: "count-only", label: "Count only", value: "", help: "Print only how many distinct combinations there are", kind: Kind::Flag },
+    Param { name: "threads", label: "Threads", value: "N", help: "Search for a sum on N threads, or 0 for one per core", kind: Kind::Value },
+    Param { name: "deterministic", label: "Deterministic", value: "", help: "With --threads, find the same combination as a single thread", kind: Kind::Flag },
+    Param { name: "part1", label: "Part 1", value: "", help: "Preset: two entries summing to 2020 from day1/data.txt", kind: Kind::Flag },
+    Param { name: "part2", label: "Part 2", value: "", help: "Preset: three entries summing to 2020 from day1/data.txt", kind: Kind::Flag },
+];
+
 impl Config {
-    pub fn new(args: Vec<String>) -> Result<Config, &'static str> {
-        let mut args = args.into_iter();
-
-        let filename = match args.next() {
-            Some(arg) => {
-                // Check for default arguments and short circuit if matched
-                match arg.as_str() {
-                    "--part1" => return Ok(Config {
-                            filename: String::from("day1/data.txt"),
-                            sum: 2020,
-                            num_variables: 2,
-                        }),
-                    "--part2" => return Ok(Config {
-                        filename: String::from("day1/data.txt"),
-                            sum: 2020,
-                            num_variables: 3,
-                        }),
-                    _ => arg,
-                }
+    pub fn new(args: &Args) -> Result<Config, Error> {
+        let mode = if args.flag("count-only") {
+            Mode::Count
+        } else if args.flag("all") {
+            Mode::All
+        } else {
+            Mode::First
+        };
+
+        let objective = match args.get("objective") {
+            None | Some("sum") => Objective::Sum,
+            Some("product") => Objective::Product,
+            Some("closest") => Objective::Closest,
+            Some("range") => Objective::Range,
+            Some(other) => return Err(Error::invalid_argument("Objective", &format!("must be sum, product, closest or range, got '{}'", other))),
+        };
+
+        if objective != Objective::Sum && mode != Mode::First {
+            return Err(Error::invalid_argument("Objective", "must be sum to use --all or --count-only"));
+        }
+
+        let number_type = match args.get("type") {
+            None | Some("i64") => NumberType::I64,
+            Some("i128") => NumberType::I128,
+            #[cfg(feature = "bigint")]
+            Some("big") => NumberType::Big,
+            #[cfg(not(feature = "bigint"))]
+            Some("big") => return Err(Error::invalid_argument("Type", "'big' needs the `bigint` feature")),
+            Some(other) => return Err(Error::invalid_argument("Type", &format!("must be i64, i128 or big, got '{}'", other))),
+        };
+
+        let threads = match args.get("threads") {
+            None => 1,
+            Some(_) => match args.integer("threads")? {
+                0 => thread::available_parallelism().map_or(1, |n| n.get()),
+                n => n,
             },
-            None => return Err("'Filename' parameter not supplied"),
         };
 
-        let sum = match args.next() {
-            Some(arg) => arg,
-            None => return Err("'Sum' parameter not supplied"),
-        }.parse();
+        if threads > 1 && (objective != Objective::Sum || mode != Mode::First) {
+            return Err(Error::invalid_argument("Threads", "only applies to finding the first sum"));
+        }
+
+        let deterministic = args.flag("deterministic");
+
+        // Check for default arguments and short circuit if matched
+        let preset = if args.flag("part1") {
+            Some(Config::part1())
+        } else if args.flag("part2") {
+            Some(Config::part2())
+        } else {
+            None
+        };
+
+        if let Some(preset) = preset {
+            return Ok(Config {
+                filename: preset_filename()?,
+                objective,
+                mode,
+                number_type,
+                threads,
+                deterministic,
+                ..preset
+            });
+        }
+
+        let filename = String::from(args.require("input")?);
+        let sum = String::from(args.require("sum")?);
+        // Whether it fits depends on the number type, so only reject text
+        // that is not an integer at all
+        if let Err(err) = sum.parse::<i128>() {
+            if !matches!(err.kind(), IntErrorKind::PosOverflow | IntErrorKind::NegOverflow) {
+                return Err(Error::bad_integer("Sum", &sum, err));
+            }
+        }
+        let num_variables = args.integer("count")?;
+
+        Ok(Config { filename, sum, num_variables, objective, mode, number_type, threads, deterministic })
+    }
 
-        let num_variables = match args.next() {
-            Some(arg) => arg,
-            None => return Err("'Number of variables' parameter not supplied"),
-        }.parse();
+    pub fn part1() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            sum: String::from("2020"),
+            num_variables: 2,
+            objective: Objective::Sum,
+            mode: Mode::First,
+            number_type: NumberType::I64,
+            threads: 1,
+            deterministic: false,
+        }
+    }
 
-        return match (sum, num_variables) {
-            (Ok(sum), Ok(num_variables)) => Ok(Config { filename, sum, num_variables }),
-            (Err(_), _) => Err("'Sum' parameter must be an integer"),
-            (_, Err(_)) => Err("'Number of variables' parameter must be an integer"),
+    pub fn part2() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            sum: String::from("2020"),
+            num_variables: 3,
+            objective: Objective::Sum,
+            mode: Mode::First,
+            number_type: NumberType::I64,
+            threads: 1,
+            deterministic: false,
         }
     }
-}
\ No newline at end of file
+}
+
+pub const INPUT: &str = "day1/data.txt";
+
+/// Locates the preset input, which need not be relative to the current
+/// directory.
+pub fn input() -> Result<Input, Error> {
+    let path = common::resolve("day1", "data.txt", env!("CARGO_MANIFEST_DIR"))?;
+    Ok(Input::Path(path))
+}
+
+fn preset_filename() -> Result<String, Error> {
+    match input()? {
+        Input::Path(path) => Ok(path.display().to_string()),
+        _ => Ok(String::from(INPUT)),
+    }
+}
@@ -0,0 +1,565 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::number::Number;
+
+/// Finds `k` entries of `numbers`, which must be sorted in ascending order,
+/// that sum to `target`. Returns the indices of the entries in ascending
+/// order, so an entry is only used as often as it appears.
+///
+/// Sums are worked out in the entries' own type, so a combination whose
+/// partial sums overflow it is not found. Use a wider type for such inputs.
+pub fn find<T: Number>(numbers: &[T], k: usize, target: &T) -> Option<Vec<usize>> {
+    debug_assert!(numbers.windows(2).all(|w| w[0] <= w[1]), "numbers must be sorted");
+
+    match k {
+        0 if T::from_i128(0).as_ref() == Some(target) => Some(Vec::new()),
+        0 => None,
+        1 => numbers.iter().position(|x| x == target).map(|i| vec![i]),
+        2 => two_sum(numbers, target).map(|(a, b)| vec![a, b]),
+        _ => k_sum(numbers, 0, k, target, &|| false),
+    }
+}
+
+/// Like [`find`], but shares out the choices of smallest entry between
+/// `threads` threads. The first combination found stops the search, unless
+/// `deterministic`, in which case only combinations with a smaller first
+/// entry are still looked for, so the result matches [`find`].
+pub fn find_parallel<T: Number>(numbers: &[T], k: usize, target: &T, threads: usize, deterministic: bool) -> Option<Vec<usize>> {
+    debug_assert!(numbers.windows(2).all(|w| w[0] <= w[1]), "numbers must be sorted");
+
+    let n = numbers.len();
+    if k < 3 || threads < 2 || n < k {
+        return find(numbers, k, target);
+    }
+
+    let next = AtomicUsize::new(0);
+    // Smallest first index of any combination found so far
+    let best = AtomicUsize::new(usize::MAX);
+    let found: Mutex<Option<Vec<usize>>> = Mutex::new(None);
+
+    let cancelled = |first: usize| match best.load(atomic::Ordering::Relaxed) {
+        usize::MAX => false,
+        best => !deterministic || best < first,
+    };
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, atomic::Ordering::Relaxed);
+                if i > n - k || cancelled(i) {
+                    break;
+                }
+
+                let x = &numbers[i];
+                if exceeds(x, k, target) {
+                    break;
+                }
+                if i > 0 && numbers[i] == numbers[i - 1] {
+                    continue;
+                }
+                if falls_short(x, &numbers[n - 1], k, target) {
+                    continue;
+                }
+
+                let rest = match target.checked_sub(x) {
+                    Some(rest) => rest,
+                    None => continue,
+                };
+
+                if let Some(mut rest) = k_sum(numbers, i + 1, k - 1, &rest, &|| cancelled(i)) {
+                    rest.insert(0, i);
+
+                    let mut found = found.lock().unwrap();
+                    if best.fetch_min(i, atomic::Ordering::Relaxed) > i {
+                        *found = Some(rest);
+                    }
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap()
+}
+
+/// Single pass remembering each entry seen so far, O(n).
+fn two_sum<T: Number>(numbers: &[T], target: &T) -> Option<(usize, usize)> {
+    let mut seen: HashMap<&T, usize> = HashMap::new();
+
+    for (i, x) in numbers.iter().enumerate() {
+        let complement = target.checked_sub(x);
+
+        if let Some(j) = complement.and_then(|c| seen.get(&c).copied()) {
+            return Some((j, i));
+        }
+        seen.entry(x).or_insert(i);
+    }
+
+    None
+}
+
+/// Sweeps inwards from both ends of `numbers[start..]`, O(n).
+fn two_pointer<T: Number>(numbers: &[T], start: usize, target: &T) -> Option<(usize, usize)> {
+    if numbers.len() < start + 2 {
+        return None;
+    }
+
+    let mut low = start;
+    let mut high = numbers.len() - 1;
+
+    while low < high {
+        match compare_sum(&numbers[low], &numbers[high], target) {
+            Ordering::Equal => return Some((low, high)),
+            Ordering::Less => low += 1,
+            Ordering::Greater => high -= 1,
+        }
+    }
+
+    None
+}
+
+/// Fixes the smallest entry and reduces to a search for `k - 1` entries after
+/// it, ending in a two pointer sweep, O(n^(k-1)).
+///
+/// Pruning compares against the bounds `k * x` and `x + (k - 1) * largest`
+/// rather than stopping once an entry passes the target, since negative
+/// entries can still bring the sum back down.
+///
+/// Gives up once `cancelled` returns true.
+fn k_sum<T: Number, C: Fn() -> bool>(numbers: &[T], start: usize, k: usize, target: &T, cancelled: &C) -> Option<Vec<usize>> {
+    if k == 2 {
+        return two_pointer(numbers, start, target).map(|(a, b)| vec![a, b]);
+    }
+
+    let n = numbers.len();
+    if n < start + k {
+        return None;
+    }
+
+    let largest = &numbers[n - 1];
+
+    for i in start..=n - k {
+        let x = &numbers[i];
+
+        // Every later entry is at least x, so the sums only grow from here
+        if exceeds(x, k, target) || cancelled() {
+            break;
+        }
+
+        // A repeated value finds nothing the first copy did not
+        if i > start && numbers[i] == numbers[i - 1] {
+            continue;
+        }
+
+        // Even the largest entries cannot make up the difference
+        if falls_short(x, largest, k, target) {
+            continue;
+        }
+
+        let rest = match target.checked_sub(x) {
+            Some(rest) => rest,
+            None => continue,
+        };
+
+        if let Some(mut rest) = k_sum(numbers, i + 1, k - 1, &rest, cancelled) {
+            rest.insert(0, i);
+            return Some(rest);
+        }
+    }
+
+    None
+}
+
+/// Compares `a + b` with `target`. A sum too large for the type is greater,
+/// and one too small is less.
+pub fn compare_sum<T: Number>(a: &T, b: &T, target: &T) -> Ordering {
+    match a.checked_add(b) {
+        Some(sum) => sum.cmp(target),
+        None if a.is_negative() => Ordering::Less,
+        None => Ordering::Greater,
+    }
+}
+
+/// Whether `k` copies of `x` sum to more than `target`.
+pub fn exceeds<T: Number>(x: &T, k: usize, target: &T) -> bool {
+    match T::from_i128(k as i128).and_then(|k| x.checked_mul(&k)) {
+        Some(total) => total > *target,
+        None => !x.is_negative(),
+    }
+}
+
+/// Whether `x` and `k - 1` copies of `largest` sum to less than `target`.
+/// Gives false when unsure, so nothing is pruned.
+fn falls_short<T: Number>(x: &T, largest: &T, k: usize, target: &T) -> bool {
+    let total = T::from_i128(k as i128 - 1)
+        .and_then(|k| largest.checked_mul(&k))
+        .and_then(|rest| x.checked_add(&rest));
+
+    match total {
+        Some(total) => total < *target,
+        None => false,
+    }
+}
+
+/// Every distinct combination of `k` entries of the sorted `numbers` that
+/// sums to `target`, as ascending indices. Combinations with the same values
+/// are only given once, using the first copy of each repeated value.
+pub fn all<'a, T: Number>(numbers: &'a [T], k: usize, target: &T) -> Box<dyn Iterator<Item = Vec<usize>> + 'a> {
+    debug_assert!(numbers.windows(2).all(|w| w[0] <= w[1]), "numbers must be sorted");
+
+    if k < 2 {
+        return Box::new(find(numbers, k, target).into_iter());
+    }
+
+    Box::new(Combinations {
+        numbers,
+        k,
+        target: target.clone(),
+        chosen: Vec::new(),
+        low: 0,
+        high: 0,
+        started: false,
+    })
+}
+
+/// Walks the combinations by fixing the smallest `k - 2` entries in turn,
+/// then sweeping the rest for every pair that completes the sum.
+struct Combinations<'a, T> {
+    numbers: &'a [T],
+    k: usize,
+    target: T,
+    chosen: Vec<usize>,
+    low: usize,
+    high: usize,
+    started: bool,
+}
+
+impl<T: Number> Combinations<'_, T> {
+    /// What the entries after those chosen must sum to, or `None` if that
+    /// does not fit the type.
+    fn remaining(&self) -> Option<T> {
+        self.chosen.iter().try_fold(self.target.clone(), |rest, i| rest.checked_sub(&self.numbers[*i]))
+    }
+
+    /// Moves on to the next choice of the smallest `k - 2` entries that could
+    /// still reach the target, and starts a sweep after them.
+    fn advance(&mut self) -> bool {
+        let numbers = self.numbers;
+        let n = numbers.len();
+        let depth = self.k - 2;
+
+        let mut next = if self.started {
+            match self.chosen.pop() {
+                Some(i) => i + 1,
+                None => return false,
+            }
+        } else {
+            self.started = true;
+            0
+        };
+
+        while self.chosen.len() < depth {
+            let start = self.chosen.last().map_or(0, |i| i + 1);
+            let needed = self.k - self.chosen.len();
+
+            let exhausted = next + needed > n || match self.remaining() {
+                Some(remaining) => exceeds(&numbers[next], needed, &remaining),
+                None => true,
+            };
+            if exhausted {
+                match self.chosen.pop() {
+                    Some(i) => next = i + 1,
+                    None => return false,
+                }
+                continue;
+            }
+
+            let repeated = next > start && numbers[next] == numbers[next - 1];
+            let unreachable = match self.remaining() {
+                Some(remaining) => falls_short(&numbers[next], &numbers[n - 1], needed, &remaining),
+                None => true,
+            };
+
+            if !repeated && !unreachable {
+                self.chosen.push(next);
+            }
+            next += 1;
+        }
+
+        self.low = next;
+        self.high = n.saturating_sub(1);
+        true
+    }
+}
+
+impl<T: Number> Iterator for Combinations<'_, T> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if !self.started && !self.advance() {
+            return None;
+        }
+
+        loop {
+            let numbers = self.numbers;
+
+            if let Some(remaining) = self.remaining() {
+                while self.low < self.high {
+                    match compare_sum(&numbers[self.low], &numbers[self.high], &remaining) {
+                        Ordering::Less => self.low += 1,
+                        Ordering::Greater => self.high -= 1,
+                        Ordering::Equal => {
+                            let mut found = self.chosen.clone();
+                            found.push(self.low);
+                            found.push(self.high);
+
+                            // Step past every copy of both values so each pair is given once
+                            let (low, high) = (&numbers[self.low], &numbers[self.high]);
+                            while self.low < self.high && numbers[self.low] == *low {
+                                self.low += 1;
+                            }
+                            while self.low < self.high && numbers[self.high] == *high {
+                                self.high -= 1;
+                            }
+
+                            return Some(found);
+                        },
+                    }
+                }
+            }
+
+            if !self.advance() {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(numbers: &[i32], indices: Option<Vec<usize>>) -> Vec<i32> {
+        indices.unwrap().into_iter().map(|i| numbers[i]).collect()
+    }
+
+    fn values64(numbers: &[i64], indices: Option<Vec<usize>>) -> Vec<i64> {
+        indices.unwrap().into_iter().map(|i| numbers[i]).collect()
+    }
+
+    #[test]
+    fn find_should_find_two() {
+        // arrange
+        let numbers = vec![299, 366, 675, 979, 1456, 1721];
+
+        // act
+        let result = find(&numbers, 2, &2020);
+
+        // assert
+        assert_eq!(vec![299, 1721], values(&numbers, result));
+    }
+
+    #[test]
+    fn find_should_find_three() {
+        // arrange
+        let numbers = vec![299, 366, 675, 979, 1456, 1721];
+
+        // act
+        let result = find(&numbers, 3, &2020);
+
+        // assert
+        assert_eq!(vec![366, 675, 979], values(&numbers, result));
+    }
+
+    #[test]
+    fn find_should_find_more_than_three() {
+        // arrange
+        let numbers = vec![1, 2, 4, 8, 16, 32, 64];
+
+        // act
+        let result = find(&numbers, 5, &(1 + 4 + 8 + 32 + 64));
+
+        // assert
+        assert_eq!(vec![1, 4, 8, 32, 64], values(&numbers, result));
+    }
+
+    #[test]
+    fn find_should_return_none_without_match() {
+        // arrange
+        let numbers = vec![1, 2, 3, 4];
+
+        // act
+        // assert
+        assert_eq!(None, find(&numbers, 2, &100));
+        assert_eq!(None, find(&numbers, 3, &100));
+        assert_eq!(None, find(&numbers, 5, &10));
+    }
+
+    #[test]
+    fn find_should_search_large_inputs() {
+        // arrange
+        let mut seed: u64 = 2020;
+        let mut numbers: Vec<i32> = (0..20_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (seed >> 33) as i32 % 1_000_000
+            })
+            .collect();
+        numbers.sort_unstable();
+
+        let target: i32 = [17, 4_000, 9_001, 15_555, 19_999].iter().map(|i| numbers[*i]).sum();
+
+        // act
+        let result = find(&numbers, 5, &target);
+
+        // assert
+        assert_eq!(target, values(&numbers, result).into_iter().sum::<i32>());
+    }
+
+    #[test]
+    fn all_should_find_every_combination() {
+        // arrange
+        let numbers = vec![1, 2, 3, 4, 5, 6];
+
+        // act
+        let result: Vec<Vec<i32>> = all(&numbers, 3, &10)
+            .map(|indices| values(&numbers, Some(indices)))
+            .collect();
+
+        // assert
+        assert_eq!(vec![vec![1, 3, 6], vec![1, 4, 5], vec![2, 3, 5]], result);
+    }
+
+    #[test]
+    fn all_should_give_repeated_values_once() {
+        // arrange
+        let numbers = vec![1, 1, 2, 2, 3, 3];
+
+        // act
+        let result: Vec<Vec<i32>> = all(&numbers, 2, &4)
+            .map(|indices| values(&numbers, Some(indices)))
+            .collect();
+
+        // assert
+        assert_eq!(vec![vec![1, 3], vec![2, 2]], result);
+    }
+
+    #[test]
+    fn all_should_match_find_for_small_counts() {
+        // arrange
+        let numbers = vec![299, 366, 675, 979, 1456, 1721];
+
+        // act
+        // assert
+        assert_eq!(find(&numbers, 1, &979), all(&numbers, 1, &979).next());
+        assert_eq!(1, all(&numbers, 0, &0).count());
+        assert_eq!(0, all(&numbers, 4, &2020).count());
+    }
+
+    #[test]
+    fn all_should_agree_with_brute_force() {
+        // arrange
+        let numbers = vec![-4, -1, -1, 0, 2, 3, 3, 5, 7];
+        let mut expected = Vec::new();
+        for a in 0..numbers.len() {
+            for b in a + 1..numbers.len() {
+                for c in b + 1..numbers.len() {
+                    for d in c + 1..numbers.len() {
+                        let combination = vec![numbers[a], numbers[b], numbers[c], numbers[d]];
+                        if combination.iter().sum::<i32>() == 5 && !expected.contains(&combination) {
+                            expected.push(combination);
+                        }
+                    }
+                }
+            }
+        }
+        expected.sort();
+
+        // act
+        let mut result: Vec<Vec<i32>> = all(&numbers, 4, &5)
+            .map(|indices| values(&numbers, Some(indices)))
+            .collect();
+        result.sort();
+
+        // assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn find_should_use_repeated_values_from_separate_entries() {
+        // arrange
+        let numbers = vec![5, 1010, 1010, 1721];
+
+        // act
+        // assert
+        assert_eq!(Some(vec![1, 2]), find(&numbers, 2, &2020));
+        assert_eq!(None, find(&numbers, 2, &3442));
+        assert_eq!(Some(vec![0, 1, 2]), find(&numbers, 3, &2025));
+        assert_eq!(1, all(&numbers, 2, &2020).count());
+    }
+
+    #[test]
+    fn find_should_handle_negative_numbers() {
+        // arrange
+        let numbers = vec![-3000, -5, 0, 1000, 2025, 4000];
+
+        // act
+        // assert
+        assert_eq!(vec![-3000, 1000, 4000], values(&numbers, find(&numbers, 3, &2000)));
+        assert_eq!(vec![-5, 2025], values(&numbers, find(&numbers, 2, &2020)));
+        assert_eq!(vec![-3000, -5, 0, 1000], values(&numbers, find(&numbers, 4, &-2005)));
+        assert_eq!(vec![vec![-3000, -5, 1000, 4000]], all(&numbers, 4, &1995)
+            .map(|indices| values(&numbers, Some(indices)))
+            .collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn find_should_not_overflow_near_type_limits() {
+        // arrange
+        let numbers = vec![-5, i64::MAX - 1, i64::MAX];
+
+        // act
+        // assert
+        assert_eq!(Some(vec![0, 1]), find(&numbers, 2, &(i64::MAX - 6)));
+        assert_eq!(None, find(&numbers, 2, &10));
+        assert_eq!(None, find(&numbers, 3, &i64::MAX));
+        assert_eq!(0, all(&numbers, 3, &i64::MAX).count());
+    }
+
+    #[test]
+    fn find_parallel_should_match_find_when_deterministic() {
+        // arrange
+        let mut seed: u64 = 16;
+        let mut numbers: Vec<i64> = (0..2_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (seed >> 33) as i64 % 10_000 - 5_000
+            })
+            .collect();
+        numbers.sort_unstable();
+
+        for target in [-12_000, 0, 7_777, 19_000] {
+            // act
+            let result = find_parallel(&numbers, 4, &target, 4, true);
+
+            // assert
+            assert_eq!(find(&numbers, 4, &target), result);
+        }
+    }
+
+    #[test]
+    fn find_parallel_should_find_a_combination() {
+        // arrange
+        let numbers: Vec<i64> = (1..=200).collect();
+
+        // act
+        let result = find_parallel(&numbers, 5, &600, 8, false);
+
+        // assert
+        assert_eq!(600, values64(&numbers, result).iter().sum::<i64>());
+        assert_eq!(None, find_parallel(&numbers, 5, &2, 8, false));
+    }
+}
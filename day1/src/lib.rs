@@ -1,113 +1,248 @@
-use std::error::Error;
-use std::io;
-
-use common;
+use common::{Answer, Args, Diagnostics, Error, Input, Param, Puzzle};
 
+mod closest;
 mod config;
+mod ksum;
+mod number;
+mod product;
+mod range;
+
+use config::{Config, Mode, NumberType, Objective};
+use number::Number;
+
+pub struct Day1;
+
+pub static PUZZLE: Day1 = Day1;
+
+pub fn register(registry: &mut common::Registry) {
+    registry.register(&PUZZLE);
+}
+
+impl Puzzle for Day1 {
+    type Data = Vec<i64>;
+
+    fn name(&self) -> &'static str {
+        "day1"
+    }
+
+    fn about(&self) -> &'static str {
+        "Find expense report entries that sum to a target"
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &config::PARAMS
+    }
+
+    fn input(&self) -> Result<Input, Error> {
+        config::input()
+    }
 
-use config::Config;
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Vec<i64>, Error> {
+        read_numbers(input, diagnostics)
+    }
+
+    fn part1(&self, numbers: &Vec<i64>) -> Result<Answer, Error> {
+        solve(numbers, &Config::part1())
+    }
+
+    fn part2(&self, numbers: &Vec<i64>) -> Result<Answer, Error> {
+        solve(numbers, &Config::part2())
+    }
 
-pub fn run(config: common::Config) -> Result<(), Box<dyn Error>> {
-    let config = Config::new(config.args)?;
-    let numbers = read_numbers(&config)?;
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+        let config = Config::new(args)?;
 
-    match find_numbers(numbers, &config) {
-        Ok(result) => {
-            println!("Numbers: {:?} Sum: {} Product: {}", result.numbers, result.sum, result.product);
-            Ok(())
-        },
-        Err(msg) => {
-            println!("Error finding numbers: {}", msg);
-            Err(msg)?
-        },
+        match config.number_type {
+            NumberType::I64 => run_as::<i64>(&config, diagnostics),
+            NumberType::I128 => run_as::<i128>(&config, diagnostics),
+            #[cfg(feature = "bigint")]
+            NumberType::Big => run_as::<num_bigint::BigInt>(&config, diagnostics),
+        }
     }
 }
 
-enum SumResult {
-    TooSmall(Vec<i32>),
-    TooBig(Vec<i32>),
-    Equal(Vec<i32>),
-    Error,
+fn run_as<T: Number>(config: &Config, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+    let numbers: Vec<T> = read_numbers(&Input::from_arg(&config.filename), diagnostics)?;
+
+    solve(&numbers, config)
 }
 
-struct SearchResult {
-    numbers: Vec<i32>,
-    sum: i32,
-    product: i32,
+/// Solves for `numbers` in input order. Every objective but `Range` searches
+/// a sorted copy.
+fn solve<T: Number>(numbers: &[T], config: &Config) -> Result<Answer, Error> {
+    if config.objective == Objective::Range {
+        return solve_range(numbers, config);
+    }
+
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+    let numbers = &sorted;
+
+    match (config.objective, config.mode) {
+        (Objective::Sum, Mode::First) => Ok(report(find_numbers(numbers, config)?)),
+        (Objective::Sum, Mode::All) => solve_all(numbers, config),
+        (Objective::Sum, Mode::Count) => Ok(Answer::new(all_numbers(numbers, config)?.count() as i64)),
+        (Objective::Product, _) => Ok(report(find_product(numbers, config)?)),
+        (Objective::Closest, _) => Ok(report(find_closest(numbers, config)?)),
+        (Objective::Range, _) => unreachable!(),
+    }
 }
 
-impl SearchResult {
-    fn new(numbers: Vec<i32>) -> SearchResult {
-        let sum: i32 = numbers.iter().sum();
-        let product: i32 = numbers.iter().product();
+/// Answers with the product of the entries found, or says it overflows.
+fn report<T: Number>(result: SearchResult<T>) -> Answer {
+    let value = match &result.product {
+        Some(product) => product.value(),
+        None => format!("Product overflows {}", T::NAME).into(),
+    };
+
+    Answer::new(value)
+        .with("Numbers", number::list(&result.numbers))
+        .with("Sum", result.sum.value())
+}
 
-        SearchResult {
+struct SearchResult<T> {
+    numbers: Vec<T>,
+    sum: T,
+    /// `None` when the product does not fit `T`.
+    product: Option<T>,
+}
+
+impl<T: Number> SearchResult<T> {
+    /// Totals the numbers, failing if the sum or product overflows `T`.
+    fn new(numbers: Vec<T>) -> Result<SearchResult<T>, Error> {
+        let result = SearchResult::run(numbers)?;
+        if result.product.is_none() {
+            return Err(Error::Overflow { what: "Product", type_name: T::NAME });
+        }
+
+        Ok(result)
+    }
+
+    /// Totals a run of entries, failing only if the sum overflows `T`. A long
+    /// run is still reported when its product does not fit.
+    fn run(numbers: Vec<T>) -> Result<SearchResult<T>, Error> {
+        let zero = T::from_i128(0).expect("every type holds 0");
+        let one = T::from_i128(1).expect("every type holds 1");
+
+        let sum = numbers.iter()
+            .try_fold(zero, |sum, n| sum.checked_add(n))
+            .ok_or(Error::Overflow { what: "Sum", type_name: T::NAME })?;
+        let product = numbers.iter()
+            .try_fold(one, |product, n| product.checked_mul(n));
+
+        Ok(SearchResult {
             numbers,
             sum,
             product,
-        }
+        })
     }
 }
 
-fn find_numbers(numbers: Vec<i32>, config: &Config) -> Result<SearchResult, &'static str> {
-    let selected: Vec<i32> = Vec::new();
-    return match find_numbers_internal(&numbers, selected, config) {
-        SumResult::Equal(numbers) => Ok(SearchResult::new(numbers)),
-        _ => Err("Could not find matching numbers"),
+/// Finds `num_variables` entries of the sorted `numbers` that add up to `sum`.
+fn find_numbers<T: Number>(numbers: &[T], config: &Config) -> Result<SearchResult<T>, Error> {
+    let target = target(config)?;
+    let indices = if config.threads > 1 {
+        ksum::find_parallel(numbers, config.num_variables, &target, config.threads, config.deterministic)
+    } else {
+        ksum::find(numbers, config.num_variables, &target)
+    };
+
+    match indices {
+        Some(indices) => SearchResult::new(indices.into_iter().map(|i| numbers[i].clone()).collect()),
+        None => Err(Error::NoSolution("Could not find matching numbers")),
     }
 }
 
-fn find_numbers_internal(numbers: &Vec<i32>, selected: Vec<i32>, config: &Config) -> SumResult {
-    let sum: i32 = selected.iter().sum();
+/// Finds `num_variables` entries of the sorted `numbers` that multiply to
+/// `sum`.
+fn find_product<T: Number>(numbers: &[T], config: &Config) -> Result<SearchResult<T>, Error> {
+    match product::find(numbers, config.num_variables, &target(config)?) {
+        Some(indices) => SearchResult::new(indices.into_iter().map(|i| numbers[i].clone()).collect()),
+        None => Err(Error::NoSolution("Could not find numbers with a matching product")),
+    }
+}
 
-    if selected.len() == config.num_variables {
-        if sum == config.sum {
-            return SumResult::Equal(selected.clone());
-        } else if sum > config.sum {
-            return SumResult::TooBig(selected.clone());
-        } else {
-            return SumResult::TooSmall(selected.clone());
-        }
+/// Finds `num_variables` entries of the sorted `numbers` that add up to as
+/// much as they can without exceeding `sum`.
+fn find_closest<T: Number>(numbers: &[T], config: &Config) -> Result<SearchResult<T>, Error> {
+    match closest::find(numbers, config.num_variables, &target(config)?) {
+        Some(indices) => SearchResult::new(indices.into_iter().map(|i| numbers[i].clone()).collect()),
+        None => Err(Error::NoSolution("Every choice of numbers exceeds the sum")),
     }
+}
 
-    for x in numbers {
-        if selected.contains(x) {
-            continue;
-        }
+/// Answers with the product of the run of entries found, and where it is.
+fn solve_range<T: Number>(numbers: &[T], config: &Config) -> Result<Answer, Error> {
+    let run = match range::find(numbers, config.num_variables, &target(config)?)? {
+        Some(run) => run,
+        None => return Err(Error::NoSolution("Could not find a run of numbers with a matching sum")),
+    };
 
-        if selected.len() == 0 {
-            println!("First number of search: {}", x);
-        }
+    let entries = format!("{}-{}", run.start + 1, run.end);
+    let result = SearchResult::run(numbers[run].to_vec())?;
+
+    Ok(report(result).with("Entries", entries))
+}
 
-        let mut new_selected = selected.clone();
-        new_selected.push(*x);
-        
-        let internal_result = find_numbers_internal(numbers, new_selected, config);
+/// Answers with how many combinations there are, listing each one.
+fn solve_all<T: Number>(numbers: &[T], config: &Config) -> Result<Answer, Error> {
+    let mut count: i64 = 0;
+    let mut answer = Answer::new(0);
 
-        match internal_result {
-            SumResult::TooBig(_) => break,
-            SumResult::Equal(numbers) => return SumResult::Equal(numbers),
-            result => result,
-        };
+    for combination in all_numbers(numbers, config)? {
+        count += 1;
+        answer = answer.with(&format!("Combination {}", count), number::list(&combination));
     }
 
-    return SumResult::Error;
+    answer.value = count.into();
+    Ok(answer)
 }
 
-fn read_numbers(config: &Config) -> io::Result<Vec<i32>> {
-    let lines = common::read_lines(&config.filename)?;
+/// Every distinct set of `num_variables` entries of the sorted `numbers` that
+/// add up to `sum`.
+fn all_numbers<'a, T: Number>(numbers: &'a [T], config: &Config) -> Result<impl Iterator<Item = Vec<T>> + 'a, Error> {
+    let combinations = ksum::all(numbers, config.num_variables, &target(config)?)
+        .map(move |indices| indices.into_iter().map(|i| numbers[i].clone()).collect());
 
-    let mut numbers: Vec<i32> = Vec::new();
-    for line in lines {
-        if let Ok(line) = line {
-            if let Ok(num) = line.parse() {
-                numbers.push(num);
-            }
+    Ok(combinations)
+}
+
+fn target<T: Number>(config: &Config) -> Result<T, Error> {
+    config.sum.parse()
+        .map_err(|_| Error::Overflow { what: "Sum", type_name: T::NAME })
+}
+
+fn read_numbers<T: Number>(input: &Input, diagnostics: &mut Diagnostics) -> Result<Vec<T>, Error> {
+    let mut numbers: Vec<T> = Vec::new();
+    for (index, line) in input.lines()?.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_number(&line) {
+            Ok(num) => numbers.push(num),
+            Err(err) => diagnostics.report(err.on_line(index + 1).in_file(&input.name()))?,
         }
     }
 
-    numbers.sort();
-    return Ok(numbers);
+    Ok(numbers)
+}
+
+fn parse_number<T: Number>(line: &str) -> Result<T, Error> {
+    if line.is_empty() {
+        return Err(Error::parse(line, 1, "expected an integer, found an empty line"));
+    }
+
+    line.parse().map_err(|_| {
+        let column = line.char_indices()
+            .position(|(i, c)| !(c.is_ascii_digit() || (i == 0 && (c == '-' || c == '+'))));
+
+        match column {
+            Some(column) => Error::parse(line, column + 1, "expected an integer"),
+            None => Error::parse(line, 1, &format!("integer does not fit in {}", T::NAME)),
+        }
+    })
 }
 
 #[cfg(test)]
@@ -121,12 +256,36 @@ mod tests {
         let numbers_clone = numbers.clone();
 
         // act
-        let result = SearchResult::new(numbers);
+        let result = SearchResult::new(numbers).unwrap();
 
         // assert
         assert_eq!(numbers_clone, result.numbers);
         assert_eq!(10, result.sum);
-        assert_eq!(24, result.product);
+        assert_eq!(Some(24), result.product);
+    }
+
+    #[test]
+    fn search_result_new_should_report_overflow() {
+        // arrange
+        let numbers: Vec<i32> = vec![1000, 2000, 3000];
+
+        // act
+        let result = SearchResult::new(numbers);
+
+        // assert
+        assert_eq!("Product overflows i32", result.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn search_result_new_should_fit_wider_types() {
+        // arrange
+        let numbers: Vec<i128> = vec![4_000_000_000, 5_000_000_000, 6_000_000_000];
+
+        // act
+        let result = SearchResult::new(numbers).unwrap();
+
+        // assert
+        assert_eq!(Some(120_000_000_000_000_000_000_000_000_000), result.product);
     }
 
     #[test]
@@ -137,12 +296,13 @@ mod tests {
             String::from("5"),
             String::from("2")];
 
-        let config = Config::new(args).unwrap();
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
 
         let numbers = vec![1, 2, 3, 5];
 
         // act
-        let result = find_numbers(numbers, &config).unwrap();
+        let result = find_numbers(&numbers, &config).unwrap();
 
         // assert
         assert_eq!(vec![2, 3], result.numbers);
@@ -157,15 +317,297 @@ mod tests {
             String::from("9"),
             String::from("3")];
 
-        let config = Config::new(args).unwrap();
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
 
         let numbers = vec![1, 2, 3, 4];
 
         // act
-        let result = find_numbers(numbers, &config).unwrap();
+        let result = find_numbers(&numbers, &config).unwrap();
 
         // assert
         assert_eq!(vec![2, 3, 4], result.numbers);
         assert_eq!(9, result.sum);
     }
+
+    #[test]
+    fn find_numbers_should_use_repeated_values() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("2020"),
+            String::from("2")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let mut numbers: Vec<i64> = read_numbers(&Input::text("1721\n1010\n299\n1010\n"), &mut Diagnostics::default()).unwrap();
+        numbers.sort_unstable();
+
+        // act
+        let result = find_numbers(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(vec![1010, 1010], result.numbers);
+    }
+
+    #[test]
+    fn find_numbers_should_handle_negatives() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("2020"),
+            String::from("3")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let mut numbers: Vec<i64> = read_numbers(&Input::text("3000\n-1000\n20\n2500\n-480\n"), &mut Diagnostics::default()).unwrap();
+        numbers.sort_unstable();
+
+        // act
+        let result = find_numbers(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(vec![-1000, 20, 3000], result.numbers);
+    }
+
+    #[test]
+    fn solve_should_list_all_combinations() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("6"),
+            String::from("2"),
+            String::from("--all")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let numbers = vec![1, 2, 3, 4, 5];
+
+        // act
+        let answer = solve(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(common::Value::Integer(2), answer.value);
+        assert_eq!(Some(&common::Value::List(vec![1, 5])), answer.detail("Combination 1"));
+        assert_eq!(Some(&common::Value::List(vec![2, 4])), answer.detail("Combination 2"));
+    }
+
+    fn wide_config(sum: &str) -> Result<Config, Error> {
+        let args = vec![String::from("_"), String::from(sum), String::from("2")];
+        Config::new(&Args::parse(&config::PARAMS, args).unwrap())
+    }
+
+    #[test]
+    fn target_should_read_sum_as_number_type() {
+        // arrange
+        let config = wide_config("170141183460469231731687303715884105728").unwrap();
+
+        // act
+        let result = target::<i128>(&config);
+
+        // assert
+        assert_eq!("Sum overflows i128", result.unwrap_err().to_string());
+        assert_eq!("'Sum' parameter must be an integer, got '12x'", wide_config("12x").err().unwrap().to_string());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn target_should_read_sum_wider_than_i128() {
+        // arrange
+        let sum = "-170141183460469231731687303715884105729";
+        let config = wide_config(sum).unwrap();
+
+        // act
+        let result = target::<num_bigint::BigInt>(&config).unwrap();
+
+        // assert
+        assert_eq!(sum, result.to_string());
+    }
+
+    #[test]
+    fn read_numbers_should_keep_input_order() {
+        // arrange
+        let input = Input::text("1721\n979\n366\n");
+
+        // act
+        let numbers: Vec<i64> = read_numbers(&input, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(vec![1721, 979, 366], numbers);
+    }
+
+    #[test]
+    fn read_numbers_should_skip_blank_lines() {
+        // arrange
+        let input = Input::text("1721\n\n979\n  \n366\n");
+
+        // act
+        let numbers: Vec<i64> = read_numbers(&input, &mut Diagnostics::new(common::Strictness::Strict)).unwrap();
+
+        // assert
+        assert_eq!(vec![1721, 979, 366], numbers);
+    }
+
+    #[test]
+    fn read_numbers_should_fail_on_unreadable_line() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("day1-invalid-{}.txt", std::process::id()));
+        std::fs::write(&path, b"1721\n\xff\xfe\n979\n").unwrap();
+
+        // act
+        let result = read_numbers::<i64>(&Input::Path(path.clone()), &mut Diagnostics::new(common::Strictness::Lenient));
+        std::fs::remove_file(&path).unwrap();
+
+        // assert
+        match result {
+            Err(Error::Io { .. }) => (),
+            Err(err) => panic!("expected an I/O error, got {}", err),
+            Ok(numbers) => panic!("expected an I/O error, got {:?}", numbers),
+        }
+    }
+
+    #[test]
+    fn solve_should_find_range_in_input_order() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("127"),
+            String::from("2"),
+            String::from("--objective=range")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let numbers: Vec<i64> = vec![35, 20, 15, 25, 47, 40, 62, 55, 65, 95];
+
+        // act
+        let answer = solve(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(common::Value::Integer(15 * 25 * 47 * 40), answer.value);
+        assert_eq!(Some(&common::Value::from("3-6")), answer.detail("Entries"));
+    }
+
+    #[test]
+    fn solve_should_find_range_whose_product_overflows() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("231"),
+            String::from("2"),
+            String::from("--objective=range")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        // 21! does not fit an i64
+        let numbers: Vec<i64> = (1..=21).collect();
+
+        // act
+        let answer = solve(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(common::Value::from("Product overflows i64"), answer.value);
+        assert_eq!(Some(&common::Value::Integer(231)), answer.detail("Sum"));
+        assert_eq!(Some(&common::Value::from("1-21")), answer.detail("Entries"));
+    }
+
+    #[test]
+    fn solve_should_find_product() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("130"),
+            String::from("3"),
+            String::from("--objective=product")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let numbers: Vec<i64> = vec![13, 7, 2, 5, 3];
+
+        // act
+        let answer = solve(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(common::Value::Integer(130), answer.value);
+        assert_eq!(Some(&common::Value::List(vec![2, 5, 13])), answer.detail("Numbers"));
+        assert_eq!(Some(&common::Value::Integer(20)), answer.detail("Sum"));
+    }
+
+    #[test]
+    fn find_numbers_should_search_on_threads() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("2020"),
+            String::from("3"),
+            String::from("--threads=4"),
+            String::from("--deterministic")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        let numbers: Vec<i64> = (0..1_000).map(|i| i * 5).collect();
+
+        // act
+        let result = find_numbers(&numbers, &config).unwrap();
+
+        // assert
+        assert_eq!(4, config.threads);
+        assert_eq!(vec![0, 5, 2015], result.numbers);
+    }
+
+    #[test]
+    fn config_new_should_reject_all_for_other_objectives() {
+        // arrange
+        let args = vec![
+            String::from("_"),
+            String::from("130"),
+            String::from("3"),
+            String::from("--objective=closest"),
+            String::from("--all")];
+
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+
+        // act
+        let result = Config::new(&args);
+
+        // assert
+        assert_eq!("'Objective' parameter must be sum to use --all or --count-only", result.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn parse_number_should_locate_bad_character() {
+        // arrange
+        let line = "12x4";
+
+        // act
+        let result = parse_number::<i64>(line);
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!(3, diagnostic.column),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn parse_number_should_report_out_of_range() {
+        // arrange
+        let line = "9223372036854775808";
+
+        // act
+        let result = parse_number::<i64>(line);
+
+        // assert
+        assert_eq!(9_223_372_036_854_775_808, parse_number::<i128>(line).unwrap());
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!("integer does not fit in i64", diagnostic.message),
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
@@ -0,0 +1,196 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use common::Value;
+
+/// An integer type entries can be read, summed and multiplied as. Arithmetic
+/// is checked, giving `None` instead of overflowing.
+pub trait Number: Clone + Ord + Hash + Debug + Display + FromStr + Send + Sync {
+    /// Name of the type in `--type` and overflow errors.
+    const NAME: &'static str;
+
+    fn from_i128(value: i128) -> Option<Self>;
+
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    /// The quotient, if `other` divides `self` exactly.
+    fn exact_div(&self, other: &Self) -> Option<Self>;
+
+    fn is_negative(&self) -> bool;
+
+    /// The number as part of an answer, written out as text when it does not
+    /// fit an `i64`.
+    fn value(&self) -> Value;
+}
+
+macro_rules! primitive {
+    ($type:ty, $name:expr) => {
+        impl Number for $type {
+            const NAME: &'static str = $name;
+
+            fn from_i128(value: i128) -> Option<Self> {
+                <$type>::try_from(value).ok()
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$type>::checked_add(*self, *other)
+            }
+
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$type>::checked_sub(*self, *other)
+            }
+
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$type>::checked_mul(*self, *other)
+            }
+
+            fn exact_div(&self, other: &Self) -> Option<Self> {
+                match <$type>::checked_rem(*self, *other) {
+                    Some(0) => <$type>::checked_div(*self, *other),
+                    _ => None,
+                }
+            }
+
+            fn is_negative(&self) -> bool {
+                *self < 0
+            }
+
+            fn value(&self) -> Value {
+                match i64::try_from(*self) {
+                    Ok(value) => Value::Integer(value),
+                    Err(_) => Value::Text(self.to_string()),
+                }
+            }
+        }
+    };
+}
+
+primitive!(i32, "i32");
+primitive!(i64, "i64");
+primitive!(i128, "i128");
+
+#[cfg(feature = "bigint")]
+impl Number for num_bigint::BigInt {
+    const NAME: &'static str = "big";
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Some(num_bigint::BigInt::from(value))
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn exact_div(&self, other: &Self) -> Option<Self> {
+        if other.sign() == num_bigint::Sign::NoSign {
+            return None;
+        }
+
+        let quotient = self / other;
+        if &quotient * other == *self {
+            Some(quotient)
+        } else {
+            None
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        self.sign() == num_bigint::Sign::Minus
+    }
+
+    fn value(&self) -> Value {
+        match i64::try_from(self) {
+            Ok(value) => Value::Integer(value),
+            Err(_) => Value::Text(self.to_string()),
+        }
+    }
+}
+
+/// The numbers as part of an answer, written out as text when any of them
+/// does not fit an `i64`.
+pub fn list<T: Number>(numbers: &[T]) -> Value {
+    let values: Vec<Value> = numbers.iter().map(Number::value).collect();
+
+    if values.iter().all(|v| matches!(v, Value::Integer(_))) {
+        return Value::List(values.into_iter()
+            .map(|v| match v {
+                Value::Integer(i) => i,
+                _ => unreachable!(),
+            })
+            .collect());
+    }
+
+    let items: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    Value::Text(format!("[{}]", items.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_mul_should_detect_overflow() {
+        // arrange
+        let x: i64 = 3_037_000_500;
+
+        // act
+        // assert
+        assert_eq!(None, Number::checked_mul(&x, &x));
+        assert_eq!(Some(9_223_372_037_000_250_000), Number::checked_mul(&i128::from(x), &i128::from(x)));
+    }
+
+    #[test]
+    fn exact_div_should_require_a_divisor() {
+        // arrange
+        let x: i64 = 12;
+
+        // act
+        // assert
+        assert_eq!(Some(-4), x.exact_div(&-3));
+        assert_eq!(None, x.exact_div(&5));
+        assert_eq!(None, x.exact_div(&0));
+        assert_eq!(None, i64::MIN.exact_div(&-1));
+    }
+
+    #[test]
+    fn value_should_fall_back_to_text() {
+        // arrange
+        let small: i128 = 42;
+        let large: i128 = i128::from(i64::MAX) + 1;
+
+        // act
+        // assert
+        assert_eq!(Value::Integer(42), small.value());
+        assert_eq!(Value::Text(String::from("9223372036854775808")), large.value());
+        assert_eq!(Value::List(vec![1, 42]), list(&[1, small]));
+        assert_eq!(Value::Text(String::from("[1, 9223372036854775808]")), list(&[1, large]));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn big_should_never_overflow() {
+        // arrange
+        let x = num_bigint::BigInt::from(i128::MAX);
+
+        // act
+        let product = Number::checked_mul(&x, &x).unwrap();
+
+        // assert
+        assert_eq!(Value::Text(product.to_string()), product.value());
+        assert!(!product.is_negative());
+    }
+}
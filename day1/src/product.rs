@@ -0,0 +1,91 @@
+use crate::number::Number;
+
+/// Finds `k` entries of `numbers`, which must be sorted in ascending order,
+/// whose product is `target`. Returns the indices of the entries in
+/// ascending order.
+pub fn find<T: Number>(numbers: &[T], k: usize, target: &T) -> Option<Vec<usize>> {
+    debug_assert!(numbers.windows(2).all(|w| w[0] <= w[1]), "numbers must be sorted");
+
+    search(numbers, 0, k, target)
+}
+
+/// Fixes the smallest entry and looks for the rest of the product after it.
+/// Only entries that divide what is left are tried.
+fn search<T: Number>(numbers: &[T], start: usize, k: usize, target: &T) -> Option<Vec<usize>> {
+    let n = numbers.len();
+    if n < start + k {
+        return None;
+    }
+
+    let zero = T::from_i128(0)?;
+
+    match k {
+        0 if T::from_i128(1).as_ref() == Some(target) => return Some(Vec::new()),
+        0 => return None,
+        1 => return numbers[start..].binary_search(target).ok().map(|i| vec![start + i]),
+        _ => {},
+    }
+
+    for i in start..=n - k {
+        // A repeated value finds nothing the first copy did not
+        if i > start && numbers[i] == numbers[i - 1] {
+            continue;
+        }
+
+        let x = &numbers[i];
+
+        // Zero makes the product zero whatever else is chosen
+        if *x == zero {
+            if *target == zero {
+                return Some((i..i + k).collect());
+            }
+            continue;
+        }
+
+        let rest = match target.exact_div(x) {
+            Some(rest) => rest,
+            None => continue,
+        };
+
+        if let Some(mut rest) = search(numbers, i + 1, k - 1, &rest) {
+            rest.insert(0, i);
+            return Some(rest);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(numbers: &[i64], indices: Option<Vec<usize>>) -> Vec<i64> {
+        indices.unwrap().into_iter().map(|i| numbers[i]).collect()
+    }
+
+    #[test]
+    fn find_should_find_product() {
+        // arrange
+        let numbers = vec![2, 3, 5, 7, 11, 13];
+
+        // act
+        // assert
+        assert_eq!(vec![7, 11], values(&numbers, find(&numbers, 2, &77)));
+        assert_eq!(vec![2, 5, 13], values(&numbers, find(&numbers, 3, &130)));
+        assert_eq!(None, find(&numbers, 2, &4));
+    }
+
+    #[test]
+    fn find_should_handle_negatives_and_zero() {
+        // arrange
+        let numbers = vec![-6, -2, 0, 3, 4];
+
+        // act
+        // assert
+        assert_eq!(vec![-6, -2], values(&numbers, find(&numbers, 2, &12)));
+        assert_eq!(vec![-6, 3], values(&numbers, find(&numbers, 2, &-18)));
+        assert_eq!(vec![-6, -2, 0], values(&numbers, find(&numbers, 3, &0)));
+        assert_eq!(vec![-2, 3, 4], values(&numbers, find(&numbers, 3, &-24)));
+    }
+}
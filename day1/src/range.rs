@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use common::Error;
+
+use crate::number::Number;
+
+/// Finds a run of at least `min_len` consecutive entries of `numbers`, in
+/// input order, that sums to `target`. Gives the run that ends first, and the
+/// longest such run if several end at the same entry.
+pub fn find<T: Number>(numbers: &[T], min_len: usize, target: &T) -> Result<Option<Range<usize>>, Error> {
+    let min_len = min_len.max(1);
+
+    // prefix[i] is the sum of the first i entries, added to as the runs
+    // ending at each entry are checked, so that an overflow past a match is
+    // never reached
+    let mut prefix: Vec<T> = Vec::with_capacity(numbers.len() + 1);
+    prefix.push(T::from_i128(0).expect("every type holds 0"));
+
+    // Earliest start for each prefix sum, only holding starts far enough back
+    let mut starts: HashMap<T, usize> = HashMap::new();
+
+    for (index, x) in numbers.iter().enumerate() {
+        let end = index + 1;
+        let sum = prefix[index].checked_add(x)
+            .ok_or(Error::Overflow { what: "Sum", type_name: T::NAME })?;
+        prefix.push(sum);
+
+        if end < min_len {
+            continue;
+        }
+        starts.entry(prefix[end - min_len].clone()).or_insert(end - min_len);
+
+        let wanted = prefix[end].checked_sub(target);
+        if let Some(start) = wanted.and_then(|w| starts.get(&w).copied()) {
+            return Ok(Some(start..end));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_should_find_contiguous_run() {
+        // arrange
+        let numbers: Vec<i64> = vec![35, 20, 15, 25, 47, 40, 62, 55, 65, 95];
+
+        // act
+        let result = find(&numbers, 2, &127).unwrap();
+
+        // assert
+        assert_eq!(Some(2..6), result);
+    }
+
+    #[test]
+    fn find_should_respect_minimum_length() {
+        // arrange
+        let numbers: Vec<i64> = vec![5, 10, -3, 3, 10];
+
+        // act
+        // assert
+        assert_eq!(Some(1..2), find(&numbers, 1, &10).unwrap());
+        assert_eq!(Some(1..4), find(&numbers, 2, &10).unwrap());
+        assert_eq!(None, find(&numbers, 6, &25).unwrap());
+    }
+
+    #[test]
+    fn find_should_report_overflow() {
+        // arrange
+        let numbers: Vec<i32> = vec![i32::MAX, 1];
+
+        // act
+        let result = find(&numbers, 1, &1);
+
+        // assert
+        assert_eq!("Sum overflows i32", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn find_should_return_run_ending_before_overflow() {
+        // arrange
+        let numbers: Vec<i32> = vec![1, 2, i32::MAX];
+
+        // act
+        let result = find(&numbers, 2, &3);
+
+        // assert
+        assert_eq!(Some(0..2), result.unwrap());
+    }
+}
@@ -1,40 +1,203 @@
+use std::thread;
+
+use common::{Args, Error, Format, Input, Kind, Param};
+
+use crate::policy::{self, Policy};
+use crate::rules::RuleSet;
+
 pub struct Config {
     pub filename: String,
-    pub part: i32,
+    pub policy: Box<dyn Policy>,
+    /// Rules from a policy file, checked in place of `policy`.
+    pub rules: Option<RuleSet>,
+    /// Whether to list each password's verdict rather than count them.
+    pub report: bool,
+    /// Only list passwords with this verdict in the report.
+    pub only: Option<bool>,
+    /// Whether to summarise the passwords as tables rather than count them.
+    pub stats: bool,
+    /// Whether to check each line as it is read rather than read them all
+    /// first.
+    pub stream: bool,
+    /// Threads to check a stream on, with 1 checking on the reading thread.
+    pub threads: usize,
+    pub format: Format,
+    /// Columns of a CSV, TSV or JSON Lines input holding the minimum,
+    /// maximum, letter and password, or only the password with a rules file.
+    pub columns: Vec<String>,
 }
 
+pub static PARAMS: [Param; 13] = [
+    Param { name: "input", label: "Filename", value: "FILE", help: "Password list, or `-` to read standard input", kind: Kind::Value },
+    Param { name: "part", label: "Part", value: "PART", help: "Policy to check: 1 for letter counts, 2 for letter positions", kind: Kind::Value },
+    Param { name: "policy", label: "Policy", value: "POLICY", help: "Policy to check by name, e.g. 'range and not none-of'", kind: Kind::Value },
+    Param { name: "rules", label: "Rules", value: "FILE", help: "Policy file of named rules, checked against one password per line", kind: Kind::Value },
+    Param { name: "report", label: "Report", value: "", help: "List every password with its verdict and the reason for it", kind: Kind::Flag },
+    Param { name: "only", label: "Only", value: "VERDICT", help: "List only valid or only invalid passwords, implies --report", kind: Kind::Value },
+    Param { name: "stats", label: "Stats", value: "", help: "Summarise letters, range widths, lengths and pass rates per letter and policy", kind: Kind::Flag },
+    Param { name: "stream", label: "Stream", value: "", help: "Check each line as it is read, for inputs too large to hold", kind: Kind::Flag },
+    Param { name: "threads", label: "Threads", value: "N", help: "Check a stream in chunks on N threads, or 0 for one per core, implies --stream", kind: Kind::Value },
+    Param { name: "format", label: "Format", value: "FORMAT", help: "Input format: text, csv, tsv or jsonl, by default from the file extension", kind: Kind::Value },
+    Param { name: "columns", label: "Columns", value: "COLUMNS", help: "Comma separated columns or keys for min, max, letter and password", kind: Kind::Value },
+    Param { name: "part1", label: "Part 1", value: "", help: "Preset: letter count policy on day2/data.txt", kind: Kind::Flag },
+    Param { name: "part2", label: "Part 2", value: "", help: "Preset: letter position policy on day2/data.txt", kind: Kind::Flag },
+];
+
 impl Config {
-    pub fn new(args: Vec<String>) -> Result<Config, &'static str> {
-        let mut args = args.into_iter();
-
-        let filename = match args.next() {
-            Some(arg) => {
-                // Check for default arguments and short circuit if matched
-                match arg.as_str() {
-                    "--part1" => return Ok(Config {
-                            filename: String::from("day2/data.txt"),
-                            part: 1
-                        }),
-                    "--part2" => return Ok(Config {
-                        filename: String::from("day2/data.txt"),
-                            part: 2
-                        }),
-                    _ => arg,
-                }
+    pub fn new(args: &Args) -> Result<Config, Error> {
+        let only = match args.get("only") {
+            None => None,
+            Some("valid") => Some(true),
+            Some("invalid") => Some(false),
+            Some(other) => return Err(Error::invalid_argument("Only", &format!("must be valid or invalid, got '{}'", other))),
+        };
+        let report = args.flag("report") || only.is_some();
+        let stats = args.flag("stats");
+
+        let threads = match args.get("threads") {
+            None => 1,
+            Some(_) => match args.integer("threads")? {
+                0 => thread::available_parallelism().map_or(1, |n| n.get()),
+                n => n,
             },
-            None => return Err("'Filename' parameter not supplied"),
         };
+        let stream = args.flag("stream") || args.get("threads").is_some();
+
+        if stream && report {
+            return Err(Error::invalid_argument("Stream", "only counts passwords, so cannot be combined with --report or --only"));
+        }
+
+        if stats && (report || stream) {
+            return Err(Error::invalid_argument("Stats", "cannot be combined with --report, --only or --stream"));
+        }
+
+        if let Some(filename) = args.get("rules") {
+            if stats {
+                return Err(Error::invalid_argument("Stats", "needs a letter and positions on each line, so cannot be combined with --rules"));
+            }
 
-        let part = match args.next() {
-            Some(arg) => arg,
-            None => return Err("'Part' parameter not supplied"),
-        }.parse::<i32>();
+            if args.flag("part1") || args.flag("part2") || args.get("part").is_some() || args.get("policy").is_some() {
+                return Err(Error::invalid_argument("Rules", "cannot be combined with a part or --policy"));
+            }
 
-        let part = match part {
-            Ok(p) => p,
-            Err(_) => return Err("'Part' parameter must be an integer"),
+            let config = Config {
+                filename: String::from(args.require("input")?),
+                policy: Box::new(policy::Range),
+                rules: Some(RuleSet::load(filename)?),
+                report,
+                only,
+                stats,
+                stream,
+                threads,
+                format: Format::Text,
+                columns: vec![String::from("password")],
+            };
+            return config.with_format(args);
+        }
+
+        // Check for default arguments and short circuit if matched
+        if args.flag("part1") {
+            return Config { filename: preset_filename()?, report, only, stats, stream, threads, ..Config::part1() }.with_format(args);
+        } else if args.flag("part2") {
+            return Config { filename: preset_filename()?, report, only, stats, stream, threads, ..Config::part2() }.with_format(args);
+        }
+
+        let filename = String::from(args.require("input")?);
+
+        // A named policy takes the place of the part's
+        let policy: Box<dyn Policy> = match args.get("policy") {
+            Some(text) => policy::parse(text)?,
+            None => match args.integer("part")? {
+                1 => Box::new(policy::Range),
+                2 => Box::new(policy::ExactlyOne),
+                _ => return Err(Error::invalid_argument("Part", "must be 1 or 2")),
+            },
         };
 
-        return Ok(Config{ filename, part });
+        Config{ filename, policy, rules: None, report, only, stats, stream, threads, format: Format::Text, columns: columns() }
+            .with_format(args)
     }
-}
\ No newline at end of file
+
+    /// Sets how records are read from the input, once its name is known.
+    fn with_format(mut self, args: &Args) -> Result<Config, Error> {
+        self.format = match args.get("format") {
+            Some(format) => Format::from_arg("Format", format)?,
+            None => Format::detect(&Input::from_arg(&self.filename)),
+        };
+
+        if let Some(columns) = args.get("columns") {
+            let expected = self.columns.len();
+            self.columns = columns.split(',').map(|c| String::from(c.trim())).collect();
+
+            if self.columns.len() != expected {
+                let names = if expected == 1 { "password" } else { "min, max, letter and password" };
+                return Err(Error::invalid_argument("Columns", &format!("must name {} column(s), for {}", expected, names)));
+            }
+        }
+
+        if self.threads > 1 && self.format != Format::Text {
+            return Err(Error::invalid_argument("Threads", "only splits text input into chunks, not csv, tsv or jsonl"));
+        }
+
+        Ok(self)
+    }
+
+    /// The policy to check, which is the rules file's if there is one.
+    pub fn policy(&self) -> &dyn Policy {
+        match &self.rules {
+            Some(rules) => rules,
+            None => self.policy.as_ref(),
+        }
+    }
+
+    pub fn part1() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            policy: Box::new(policy::Range),
+            rules: None,
+            report: false,
+            only: None,
+            stats: false,
+            stream: false,
+            threads: 1,
+            format: Format::Text,
+            columns: columns(),
+        }
+    }
+
+    pub fn part2() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            policy: Box::new(policy::ExactlyOne),
+            rules: None,
+            report: false,
+            only: None,
+            stats: false,
+            stream: false,
+            threads: 1,
+            format: Format::Text,
+            columns: columns(),
+        }
+    }
+}
+
+pub const INPUT: &str = "day2/data.txt";
+
+/// Default columns of a CSV, TSV or JSON Lines input.
+fn columns() -> Vec<String> {
+    ["min", "max", "letter", "password"].iter().map(|c| String::from(*c)).collect()
+}
+
+/// Locates the preset input, which need not be relative to the current
+/// directory.
+pub fn input() -> Result<Input, Error> {
+    let path = common::resolve("day2", "data.txt", env!("CARGO_MANIFEST_DIR"))?;
+    Ok(Input::Path(path))
+}
+
+fn preset_filename() -> Result<String, Error> {
+    match input()? {
+        Input::Path(path) => Ok(path.display().to_string()),
+        _ => Ok(String::from(INPUT)),
+    }
+}
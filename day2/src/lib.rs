@@ -1,106 +1,275 @@
-use std::error::Error;
+use std::borrow::Cow;
+use std::fmt;
+
+use common::{Answer, Args, Diagnostics, Error, Format, Input, Param, Puzzle};
+use unicode_segmentation::UnicodeSegmentation;
 
 mod config;
+mod parser;
+mod policy;
+mod rules;
+mod stats;
+mod stream;
 
 use config::Config;
+use policy::Policy;
+use stream::Tally;
+
+pub struct Day2;
 
-pub fn run(config: common::Config) -> Result<(), Box<dyn Error>> {
-    let config = Config::new(config.args)?;
+pub static PUZZLE: Day2 = Day2;
 
-    let passwords = read_passwords(&config)?;
+pub fn register(registry: &mut common::Registry) {
+    registry.register(&PUZZLE);
+}
 
-    let count = match config.part {
-        1 => count_valid_passwords(&passwords),
-        2 => count_valid_passwords2(&passwords),
-        _ => return Err("Invalid 'Part' parameter")?
-    };
-    println!("Valid passwords {}", count);
+impl Puzzle for Day2 {
+    type Data = Vec<Password<'static>>;
 
-    Ok(())
+    fn name(&self) -> &'static str {
+        "day2"
+    }
+
+    fn about(&self) -> &'static str {
+        "Count passwords that meet their policy"
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &config::PARAMS
+    }
+
+    fn input(&self) -> Result<Input, Error> {
+        config::input()
+    }
+
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Vec<Password<'static>>, Error> {
+        read_passwords(input, diagnostics)
+    }
+
+    fn part1(&self, passwords: &Vec<Password<'static>>) -> Result<Answer, Error> {
+        solve(passwords, &Config::part1())
+    }
+
+    fn part2(&self, passwords: &Vec<Password<'static>>) -> Result<Answer, Error> {
+        solve(passwords, &Config::part2())
+    }
+
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+        let config = Config::new(args)?;
+        let input = Input::from_arg(&config.filename);
+
+        if config.format != Format::Text {
+            return solve_records(&input, &config, diagnostics);
+        }
+
+        if config.stream {
+            let tally = stream::count(input.reader()?, &input.name(), &config, diagnostics)?;
+            return Ok(tally.answer(&config));
+        }
+
+        // Rules from a file only look at the password, so lines hold nothing else
+        let passwords = match config.rules {
+            Some(_) => read_plain_passwords(&input)?,
+            None => read_passwords(&input, diagnostics)?,
+        };
+
+        solve(&passwords, &config)
+    }
 }
 
-fn count_valid_passwords(passwords: &Vec<Password>) -> i32 {
-    let mut count = 0;
+fn solve(passwords: &[Password], config: &Config) -> Result<Answer, Error> {
+    if config.stats {
+        return stats::report(passwords, config);
+    }
+
+    if config.report {
+        return Ok(report(passwords, config));
+    }
+
+    let mut tally = Tally::new(config);
     for password in passwords {
-        let letter_count = password.value.matches(password.letter).count();
+        tally.add(password, config);
+    }
+
+    Ok(tally.answer(config))
+}
+
+/// Counts the passwords whose letter appears within their range, the part 1
+/// policy.
+pub fn count_valid_passwords(passwords: &[Password]) -> i32 {
+    count_valid(passwords, &policy::Range)
+}
+
+/// Counts the passwords whose letter is at exactly one of their two
+/// positions, the part 2 policy.
+pub fn count_valid_passwords2(passwords: &[Password]) -> i32 {
+    count_valid(passwords, &policy::ExactlyOne)
+}
 
-        if letter_count >= password.min && letter_count <= password.max {
-            count += 1;
+fn count_valid(passwords: &[Password], policy: &dyn Policy) -> i32 {
+    passwords.iter().filter(|password| policy.check(password)).count() as i32
+}
+
+/// Lists each password with its verdict and the reason for it, one per line
+/// as tab separated `file:line`, verdict, policy, password and reason.
+fn report(passwords: &[Password], config: &Config) -> Answer {
+    let policy = config.policy();
+    let mut lines: Vec<String> = Vec::new();
+    let mut valid: i64 = 0;
+
+    for password in passwords {
+        let passes = policy.check(password);
+        if passes {
+            valid += 1;
         }
+
+        if config.only.is_some_and(|only| only != passes) {
+            continue;
+        }
+
+        let verdict = if passes { "valid" } else { "invalid" };
+        lines.push(format!("{}:{}\t{}\t{}\t{}\t{}",
+            config.filename, password.line, verdict, policy, password, policy.reason(password)));
     }
-    return count;
+
+    Answer::new(lines.join("\n"))
+        .with("Valid", valid)
+        .with("Invalid", passwords.len() as i64 - valid)
+        .with("Policy", policy.to_string())
 }
 
-fn count_valid_passwords2(passwords: &Vec<Password>) -> i32 {
-    let mut count = 0;
-    for password in passwords {
-        let mut value = password.value.chars();
+/// Checks the passwords of a CSV, TSV or JSON Lines input, keeping them only
+/// when they are to be listed.
+fn solve_records(input: &Input, config: &Config, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+    if config.stream {
+        let mut tally = Tally::new(config);
+        read_records(input, config, diagnostics, |password| tally.add(password, config))?;
+        return Ok(tally.answer(config));
+    }
 
-        let min_letter = match value.nth(password.min - 1) {
-            Some(c) => c,
-            None => continue,
+    let mut passwords: Vec<Password<'static>> = Vec::new();
+    read_records(input, config, diagnostics, |password| passwords.push(password.clone().into_owned()))?;
+
+    solve(&passwords, config)
+}
+
+/// Reads a password from the configured columns of each record, handing it
+/// to `each` while it still borrows from the record.
+fn read_records<F>(input: &Input, config: &Config, diagnostics: &mut Diagnostics, mut each: F) -> Result<(), Error>
+where F: FnMut(&Password), {
+    let name = input.name();
+
+    for record in common::read_records(input, config.format, &config.columns)? {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                diagnostics.report(err.in_file(&name))?;
+                continue;
+            },
         };
-        let max_letter = match value.nth(password.max - password.min - 1) {
-            Some(c) => c,
-            None => continue,
+
+        // Rules from a file only look at the password, so only it is read
+        let password = match config.rules {
+            Some(_) => Ok(Password::plain(record.line, &record.fields[0])),
+            None => parser::record(&record),
         };
 
-        if min_letter == password.letter && max_letter != password.letter {
-            count += 1;
-        } else if min_letter != password.letter && max_letter == password.letter {
-            count += 1;
+        match password {
+            Ok(password) => each(&password),
+            Err(err) => diagnostics.report(err.in_file(&name))?,
         }
     }
-    return count;
+
+    Ok(())
 }
 
-fn read_passwords(config: &Config) -> Result<Vec<Password>, Box<dyn Error>> {
-    match common::read_lines(&config.filename) {
-        Ok(lines) => {
-            let mut passwords: Vec<Password> = Vec::new();
-            
-            for line in lines {
-                passwords.push(Password::new(line?)?);
-            }
+/// Reads one password per line, skipping blank lines.
+fn read_plain_passwords(input: &Input) -> Result<Vec<Password<'static>>, Error> {
+    let mut passwords: Vec<Password<'static>> = Vec::new();
+
+    for (index, line) in input.lines()?.enumerate() {
+        let line = line?;
 
-            return Ok(passwords);
-        },
-        Err(err) => return Err(err.into()),
+        if !line.trim().is_empty() {
+            passwords.push(Password::plain(index + 1, &line).into_owned());
+        }
     }
+
+    Ok(passwords)
 }
 
-struct Password {
+fn read_passwords(input: &Input, diagnostics: &mut Diagnostics) -> Result<Vec<Password<'static>>, Error> {
+    let mut passwords: Vec<Password<'static>> = Vec::new();
+
+    for (index, line) in input.lines()?.enumerate() {
+        match Password::new(line?) {
+            Ok(password) => passwords.push(Password { line: index + 1, ..password }),
+            Err(err) => diagnostics.report(err.on_line(index + 1).in_file(&input.name()))?,
+        }
+    }
+
+    Ok(passwords)
+}
+
+/// A password and the numbers and letter its line gives for a policy. Plain
+/// passwords, checked against a rules file, have no letter and both numbers 0.
+///
+/// The letter and password borrow from the line they were parsed from, so
+/// streaming needs no allocation per line; [`Password::into_owned`] keeps them.
+#[derive(Clone)]
+pub struct Password<'a> {
+    /// 1-based line the password was read from, or 0 if not known.
+    line: usize,
     min: usize,
     max: usize,
-    letter: char,
-    value: String,
+    /// A single grapheme cluster, which may be several characters.
+    letter: Cow<'a, str>,
+    value: Cow<'a, str>,
 }
 
-impl Password {
-    fn new(line: String) -> Result<Password, &'static str> {
-        let space_split: Vec<&str> = line.split(' ').collect();
-
-        if space_split.len() < 3 {
-            return Err("Invalid input format");
+impl fmt::Display for Password<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.letter.is_empty() {
+            return write!(f, "{}", self.value);
         }
 
-        let nums: Vec<&str> = space_split[0].split('-').collect();
+        write!(f, "{}-{} {}: {}", self.min, self.max, self.letter, self.value)
+    }
+}
 
-        let min = nums[0].parse::<usize>();
-        let max = nums[1].parse::<usize>();
+impl<'a> Password<'a> {
+    /// How many times the letter appears in the password.
+    fn occurrences(&self) -> usize {
+        self.value.graphemes(true).filter(|g| *g == self.letter).count()
+    }
 
-        match (min, max) {
-            (Ok(min), Ok(max)) => {
-                let letter = match space_split[1].chars().nth(0) {
-                    Some(c) => c,
-                    None => return Err("Invalid character"),
-                };
+    /// How many grapheme clusters long the password is.
+    fn length(&self) -> usize {
+        self.value.graphemes(true).count()
+    }
 
-                let value = String::from(space_split[2]);
+    /// Whether the letter is at the 1-based `position` of the password,
+    /// counting grapheme clusters.
+    fn letter_at(&self, position: usize) -> bool {
+        position > 0 && self.value.graphemes(true).nth(position - 1) == Some(self.letter.as_ref())
+    }
+
+    fn new(line: String) -> Result<Password<'static>, Error> {
+        parser::password(&line).map(Password::into_owned)
+    }
 
-                Ok(Password { min, max, letter, value })
-            }
-            _ => Err("Missing min or max value")
+    fn plain(line: usize, value: &'a str) -> Password<'a> {
+        Password { line, min: 0, max: 0, letter: Cow::Borrowed(""), value: Cow::Borrowed(value) }
+    }
+
+    /// Copies out whatever is borrowed from the line.
+    fn into_owned(self) -> Password<'static> {
+        Password {
+            line: self.line,
+            min: self.min,
+            max: self.max,
+            letter: Cow::Owned(self.letter.into_owned()),
+            value: Cow::Owned(self.value.into_owned()),
         }
     }
 }
@@ -186,4 +355,104 @@ mod tests {
         // assert
         assert_eq!(0, result);
     }
+
+    #[test]
+    fn read_passwords_should_read_text_input() {
+        // arrange
+        let input = Input::text("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n");
+
+        // act
+        let passwords = read_passwords(&input, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(3, passwords.len());
+        assert_eq!(2, count_valid_passwords(&passwords));
+        assert_eq!(1, count_valid_passwords2(&passwords));
+    }
+
+    #[test]
+    fn report_should_list_invalid_passwords() {
+        // arrange
+        let input = Input::text("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n");
+        let passwords = read_passwords(&input, &mut Diagnostics::default()).unwrap();
+
+        let args = vec![
+            String::from("pw.txt"),
+            String::from("2"),
+            String::from("--only=invalid")];
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        // act
+        let answer = report(&passwords, &config);
+
+        // assert
+        let expected = "pw.txt:2\tinvalid\texactly-one\t1-3 b: cdefg\tneither position 1 nor 3 contains 'b'\n\
+            pw.txt:3\tinvalid\texactly-one\t2-9 c: ccccccccc\tpositions 2 and 9 both contain 'c'";
+        assert_eq!(common::Value::from(expected), answer.value);
+        assert_eq!(Some(&common::Value::Integer(1)), answer.detail("Valid"));
+        assert_eq!(Some(&common::Value::Integer(2)), answer.detail("Invalid"));
+    }
+
+    #[test]
+    fn solve_should_count_each_rule() {
+        // arrange
+        let passwords = read_plain_passwords(&Input::text("hunter42\n\nhunter2\ncorrect horse\n")).unwrap();
+        let rules = "[[rule]]\nname = \"Long\"\nlength = { min = 8 }\n\n\
+            [[rule]]\nname = \"Digit\"\ncount = { class = \"digit\", min = 1 }\n";
+
+        let path = std::env::temp_dir().join(format!("day2-rules-{}.toml", std::process::id()));
+        std::fs::write(&path, rules).unwrap();
+
+        let args = vec![
+            String::from("-"),
+            format!("--rules={}", path.display())];
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // act
+        let answer = solve(&passwords, &config).unwrap();
+
+        // assert
+        assert_eq!(3, passwords.len());
+        assert_eq!(common::Value::Integer(1), answer.value);
+        assert_eq!(Some(&common::Value::from("2 passed, 1 failed")), answer.detail("Long"));
+        assert_eq!(Some(&common::Value::from("2 passed, 1 failed")), answer.detail("Digit"));
+    }
+
+    #[test]
+    fn solve_records_should_read_configured_columns() {
+        // arrange
+        let input = Input::text("user\tlow\thigh\tchar\tsecret\nann\t1\t3\ta\tabcde\nbob\t1\t3\tb\tcdefg\ncat\t2\t9\tc\tccccccccc\n");
+
+        let args = vec![
+            String::from("-"),
+            String::from("1"),
+            String::from("--format=tsv"),
+            String::from("--columns=low,high,char,secret")];
+        let args = Args::parse(&config::PARAMS, args).unwrap();
+        let config = Config::new(&args).unwrap();
+
+        // act
+        let answer = solve_records(&input, &config, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(common::Value::Integer(2), answer.value);
+    }
+
+    #[test]
+    fn password_new_should_describe_format() {
+        // arrange
+        let line = String::from("1-3 a");
+
+        // act
+        let result = Password::new(line);
+
+        // assert
+        match result {
+            Err(err) => assert_eq!("expected ':' after the letter", err.to_string()),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
 }
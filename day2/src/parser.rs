@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+
+use common::{Error, Record};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Password;
+
+/// Parses a `min-max letter: password` line. Whitespace may surround each
+/// part, the letter may be any single grapheme cluster, and `min` and `max`
+/// are 1-based with `min <= max`.
+pub fn password(line: &str) -> Result<Password<'_>, Error> {
+    let mut cursor = Cursor { line, at: 0 };
+
+    cursor.skip_whitespace();
+    let min_column = cursor.column();
+    let min = cursor.number("expected a minimum, as in 'min-max letter: password'")?;
+    if min == 0 {
+        return Err(Error::parse(line, min_column, "positions are 1-based, found 0"));
+    }
+
+    cursor.expect('-', "expected '-' between the minimum and maximum")?;
+
+    cursor.skip_whitespace();
+    let max_column = cursor.column();
+    let max = cursor.number("expected a maximum after '-'")?;
+    if max < min {
+        return Err(Error::parse(line, max_column, &format!("maximum must be at least the minimum, {}", min)));
+    }
+
+    cursor.skip_whitespace();
+    let letter = match cursor.grapheme() {
+        Some(g) if g != ":" && !g.trim().is_empty() => g,
+        _ => return Err(cursor.error("expected a letter")),
+    };
+    cursor.at += letter.len();
+
+    cursor.expect(':', "expected ':' after the letter")?;
+
+    cursor.skip_whitespace();
+    let value = cursor.rest().trim_end();
+    if value.is_empty() {
+        return Err(cursor.error("expected a password after ':'"));
+    }
+    if let Some(space) = value.find(char::is_whitespace) {
+        cursor.at += space;
+        return Err(cursor.error("password must not contain whitespace"));
+    }
+
+    Ok(Password { line: 0, min, max, letter: Cow::Borrowed(letter), value: Cow::Borrowed(value) })
+}
+
+/// Reads a password from a record whose fields are the minimum, maximum,
+/// letter and password, checked as for a line. The password is taken as it
+/// is, since the format already says where it ends.
+pub fn record(record: &Record) -> Result<Password<'_>, Error> {
+    let number = |field: usize, name: &str| {
+        let text = record.fields[field].trim();
+        text.parse::<usize>().map_err(|_| record.error(field, &format!("expected a {}, found '{}'", name, text)))
+    };
+
+    let min = number(0, "minimum")?;
+    if min == 0 {
+        return Err(record.error(0, "positions are 1-based, found 0"));
+    }
+
+    let max = number(1, "maximum")?;
+    if max < min {
+        return Err(record.error(1, &format!("maximum must be at least the minimum, {}", min)));
+    }
+
+    let letter = record.fields[2].trim();
+    let mut graphemes = letter.graphemes(true);
+    match (graphemes.next(), graphemes.next()) {
+        (Some(g), None) if !g.trim().is_empty() => (),
+        _ => return Err(record.error(2, &format!("expected a single letter, found '{}'", letter))),
+    }
+
+    let value = record.fields[3].as_str();
+    if value.is_empty() {
+        return Err(record.error(3, "expected a password"));
+    }
+
+    Ok(Password { line: record.line, min, max, letter: Cow::Borrowed(letter), value: Cow::Borrowed(value) })
+}
+
+/// Position in a line being parsed, as a byte offset.
+struct Cursor<'a> {
+    line: &'a str,
+    at: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.line[self.at..]
+    }
+
+    /// 1-based column of the cursor, counted in characters.
+    fn column(&self) -> usize {
+        self.line[..self.at].chars().count() + 1
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::parse(self.line, self.column(), message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    fn grapheme(&self) -> Option<&'a str> {
+        self.rest().graphemes(true).next()
+    }
+
+    fn number(&mut self, message: &str) -> Result<usize, Error> {
+        let rest = self.rest();
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+
+        if digits == 0 {
+            return Err(self.error(message));
+        }
+
+        let number = rest[..digits].parse().map_err(|_| self.error("number is too large"))?;
+        self.at += digits;
+        Ok(number)
+    }
+
+    fn expect(&mut self, expected: char, message: &str) -> Result<(), Error> {
+        self.skip_whitespace();
+
+        if self.rest().starts_with(expected) {
+            self.at += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(line: &str) -> (usize, String) {
+        match password(line) {
+            Err(Error::Parse(diagnostic)) => (diagnostic.column, diagnostic.message),
+            _ => panic!("expected a parse error for '{}'", line),
+        }
+    }
+
+    #[test]
+    fn password_should_tolerate_whitespace() {
+        // arrange
+        let line = "  1 -  3   a :  abcde \t";
+
+        // act
+        let password = password(line).unwrap();
+
+        // assert
+        assert_eq!("1-3 a: abcde", password.to_string());
+    }
+
+    #[test]
+    fn password_should_read_grapheme_letters() {
+        // arrange
+        let line = "2-3 e\u{301}: ae\u{301}xe\u{301}";
+
+        // act
+        let password = password(line).unwrap();
+
+        // assert
+        assert_eq!("e\u{301}", password.letter);
+        assert_eq!(2, password.occurrences());
+        assert!(password.letter_at(2) && password.letter_at(4));
+        assert!(!password.letter_at(3) && !password.letter_at(5));
+        assert!(!password.letter_at(0));
+    }
+
+    #[test]
+    fn password_should_locate_mistakes() {
+        // arrange
+        // act
+        // assert
+        assert_eq!((3, String::from("expected '-' between the minimum and maximum")), failure("3 a: abc"));
+        assert_eq!((4, String::from("expected a maximum after '-'")), failure("3- a: abc"));
+        assert_eq!((3, String::from("maximum must be at least the minimum, 5")), failure("5-3 a: abc"));
+        assert_eq!((1, String::from("positions are 1-based, found 0")), failure("0-3 a: abc"));
+        assert_eq!((5, String::from("expected a letter")), failure("1-3 : abc"));
+        assert_eq!((6, String::from("expected ':' after the letter")), failure("1-3 ab: abc"));
+        assert_eq!((7, String::from("expected a password after ':'")), failure("1-3 a:"));
+        assert_eq!((10, String::from("password must not contain whitespace")), failure("1-3 a: ab c"));
+        assert_eq!((1, String::from("expected a minimum, as in 'min-max letter: password'")), failure(""));
+    }
+
+    #[test]
+    fn password_should_count_columns_in_characters() {
+        // arrange
+        let line = "1-2 é é: abc";
+
+        // act
+        let (column, _) = failure(line);
+
+        // assert
+        assert_eq!(7, column);
+    }
+
+    #[test]
+    fn record_should_point_at_bad_field() {
+        // arrange
+        let input = common::Input::text("1,3,a,abc de\n3,2,a,abc\n1,3,ab,abc\n");
+        let columns: Vec<String> = ["1", "2", "3", "4"].iter().map(|c| String::from(*c)).collect();
+
+        // act
+        let results: Vec<String> = common::read_records(&input, common::Format::Csv, &columns)
+            .unwrap()
+            .map(|r| match record(&r.unwrap()) {
+                Ok(password) => password.to_string(),
+                Err(err) => err.to_string(),
+            })
+            .collect();
+
+        // assert
+        assert_eq!("1-3 a: abc de", results[0]);
+        assert_eq!("2:3: maximum must be at least the minimum, 3", results[1]);
+        assert_eq!("3:5: expected a single letter, found 'ab'", results[2]);
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use common::Error;
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Password;
+
+/// A rule a password must follow, using the numbers and letter given on its
+/// line.
+pub trait Policy: fmt::Display + Send + Sync {
+    fn check(&self, password: &Password) -> bool;
+
+    /// Why the password passes or fails, e.g. "letter 'c' appears 9 times,
+    /// allowed 2-8".
+    fn reason(&self, password: &Password) -> String;
+}
+
+/// The letter appears at least `min` and at most `max` times.
+pub struct Range;
+
+/// The letter is at exactly one of positions `min` and `max`.
+pub struct ExactlyOne;
+
+/// The letter is at position `min`, position `max` or both.
+pub struct AtLeastOne;
+
+/// The letter is at neither position `min` nor position `max`.
+pub struct NoneOf;
+
+/// The password matches a regular expression anywhere.
+pub struct Matches(Regex);
+
+/// The password has at least this many different characters.
+pub struct MinDistinct(usize);
+
+pub struct And(Box<dyn Policy>, Box<dyn Policy>);
+
+pub struct Or(Box<dyn Policy>, Box<dyn Policy>);
+
+pub struct Not(Box<dyn Policy>);
+
+impl Policy for Range {
+    fn check(&self, password: &Password) -> bool {
+        let count = password.occurrences();
+        count >= password.min && count <= password.max
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        let count = password.occurrences();
+        let times = if count == 1 { "time" } else { "times" };
+
+        format!("letter '{}' appears {} {}, allowed {}-{}", password.letter, count, times, password.min, password.max)
+    }
+}
+
+impl Policy for ExactlyOne {
+    fn check(&self, password: &Password) -> bool {
+        password.letter_at(password.min) != password.letter_at(password.max)
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        positions(password)
+    }
+}
+
+impl Policy for AtLeastOne {
+    fn check(&self, password: &Password) -> bool {
+        password.letter_at(password.min) || password.letter_at(password.max)
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        positions(password)
+    }
+}
+
+impl Policy for NoneOf {
+    fn check(&self, password: &Password) -> bool {
+        !password.letter_at(password.min) && !password.letter_at(password.max)
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        positions(password)
+    }
+}
+
+impl Policy for Matches {
+    fn check(&self, password: &Password) -> bool {
+        self.0.is_match(&password.value)
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        if self.check(password) {
+            format!("matches '{}'", self.0)
+        } else {
+            format!("does not match '{}'", self.0)
+        }
+    }
+}
+
+impl Policy for MinDistinct {
+    fn check(&self, password: &Password) -> bool {
+        distinct(password) >= self.0
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        let count = distinct(password);
+        let characters = if count == 1 { "character" } else { "characters" };
+
+        format!("has {} distinct {}, needs at least {}", count, characters, self.0)
+    }
+}
+
+impl Policy for And {
+    fn check(&self, password: &Password) -> bool {
+        self.0.check(password) && self.1.check(password)
+    }
+
+    /// Gives the reasons of whichever sides fail, or of both if neither does.
+    fn reason(&self, password: &Password) -> String {
+        let valid = self.check(password);
+        reasons(&[self.0.as_ref(), self.1.as_ref()], password, |side| valid || !side.check(password))
+    }
+}
+
+impl Policy for Or {
+    fn check(&self, password: &Password) -> bool {
+        self.0.check(password) || self.1.check(password)
+    }
+
+    /// Gives the reasons of whichever sides pass, or of both if neither does.
+    fn reason(&self, password: &Password) -> String {
+        let valid = self.check(password);
+        reasons(&[self.0.as_ref(), self.1.as_ref()], password, |side| !valid || side.check(password))
+    }
+}
+
+impl Policy for Not {
+    fn check(&self, password: &Password) -> bool {
+        !self.0.check(password)
+    }
+
+    fn reason(&self, password: &Password) -> String {
+        self.0.reason(password)
+    }
+}
+
+/// Describes what is at the two positions the password's numbers give.
+fn positions(password: &Password) -> String {
+    let (min, max, letter) = (password.min, password.max, &password.letter);
+
+    match (password.letter_at(min), password.letter_at(max)) {
+        (true, true) => format!("positions {} and {} both contain '{}'", min, max, letter),
+        (true, false) => format!("position {} contains '{}', position {} does not", min, letter, max),
+        (false, true) => format!("position {} contains '{}', position {} does not", max, letter, min),
+        (false, false) => format!("neither position {} nor {} contains '{}'", min, max, letter),
+    }
+}
+
+/// How many different grapheme clusters the password has.
+fn distinct(password: &Password) -> usize {
+    password.value.graphemes(true).collect::<HashSet<&str>>().len()
+}
+
+/// Joins the reasons of the sides of a combined policy that `include` picks.
+fn reasons<F>(sides: &[&dyn Policy], password: &Password, include: F) -> String
+where F: Fn(&dyn Policy) -> bool {
+    let reasons: Vec<String> = sides.iter()
+        .filter(|side| include(**side))
+        .map(|side| side.reason(password))
+        .collect();
+
+    reasons.join("; ")
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "range")
+    }
+}
+
+impl fmt::Display for ExactlyOne {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exactly-one")
+    }
+}
+
+impl fmt::Display for AtLeastOne {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at-least-one")
+    }
+}
+
+impl fmt::Display for NoneOf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "none-of")
+    }
+}
+
+impl fmt::Display for Matches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "regex:'{}'", self.0)
+    }
+}
+
+impl fmt::Display for MinDistinct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "min-distinct:{}", self.0)
+    }
+}
+
+impl fmt::Display for And {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} and {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for Or {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} or {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for Not {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not {}", self.0)
+    }
+}
+
+/// Parses a policy such as `range and not (regex:^a or min-distinct:3)`.
+/// `not` binds tightest, then `and`, then `or`. A regex runs to the next
+/// space unless quoted, e.g. `regex:'a b'`.
+pub fn parse(text: &str) -> Result<Box<dyn Policy>, Error> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, position: 0 };
+
+    let policy = parser.or()?;
+    match parser.next() {
+        None => Ok(policy),
+        Some(token) => Err(invalid(&format!("has {} after the end", token))),
+    }
+}
+
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+    Regex(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Regex(pattern) => write!(f, "'regex:{}'", pattern),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c == '(' {
+            tokens.push(Token::Open);
+            1
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            1
+        } else if let Some(pattern) = rest.strip_prefix("regex:") {
+            let (pattern, length) = match pattern.chars().next() {
+                Some(quote) if quote == '\'' || quote == '"' => match pattern[1..].find(quote) {
+                    Some(end) => (&pattern[1..end + 1], end + 2),
+                    None => return Err(invalid("has a regex with no closing quote")),
+                },
+                _ => {
+                    let end = pattern.find(char::is_whitespace).unwrap_or(pattern.len());
+                    (&pattern[..end], end)
+                },
+            };
+
+            tokens.push(Token::Regex(pattern));
+            "regex:".len() + length
+        } else {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '(' || c == ')').unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            end
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Word(w)) if *w == word)
+    }
+
+    fn or(&mut self) -> Result<Box<dyn Policy>, Error> {
+        let mut policy = self.and()?;
+        while self.peek_word("or") {
+            self.position += 1;
+            policy = Box::new(Or(policy, self.and()?));
+        }
+        Ok(policy)
+    }
+
+    fn and(&mut self) -> Result<Box<dyn Policy>, Error> {
+        let mut policy = self.not()?;
+        while self.peek_word("and") {
+            self.position += 1;
+            policy = Box::new(And(policy, self.not()?));
+        }
+        Ok(policy)
+    }
+
+    fn not(&mut self) -> Result<Box<dyn Policy>, Error> {
+        if self.peek_word("not") {
+            self.position += 1;
+            return Ok(Box::new(Not(self.not()?)));
+        }
+        self.rule()
+    }
+
+    fn rule(&mut self) -> Result<Box<dyn Policy>, Error> {
+        match self.next() {
+            Some(Token::Open) => {
+                let policy = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(policy),
+                    _ => Err(invalid("is missing a ')'")),
+                }
+            },
+            Some(Token::Regex(pattern)) => match Regex::new(pattern) {
+                Ok(regex) => Ok(Box::new(Matches(regex))),
+                Err(err) => Err(invalid(&format!("has an invalid regex: {}", err))),
+            },
+            Some(Token::Word(word)) => named(word),
+            Some(Token::Close) => Err(invalid("expected a rule, found ')'")),
+            None => Err(invalid("expected a rule at the end")),
+        }
+    }
+}
+
+/// Looks up a rule that is not a regex by name.
+fn named(word: &str) -> Result<Box<dyn Policy>, Error> {
+    if let Some(count) = word.strip_prefix("min-distinct:") {
+        return match count.parse() {
+            Ok(count) => Ok(Box::new(MinDistinct(count))),
+            Err(_) => Err(invalid(&format!("needs a number for min-distinct, got '{}'", count))),
+        };
+    }
+
+    match word {
+        "range" => Ok(Box::new(Range)),
+        "exactly-one" => Ok(Box::new(ExactlyOne)),
+        "at-least-one" => Ok(Box::new(AtLeastOne)),
+        "none-of" => Ok(Box::new(NoneOf)),
+        _ => Err(invalid(&format!("has unknown rule '{}'", word))),
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::invalid_argument("Policy", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn password(line: &str) -> Password<'static> {
+        Password::new(String::from(line)).unwrap()
+    }
+
+    #[test]
+    fn built_ins_should_check_positions() {
+        // arrange
+        let both = password("1-3 a: abade");
+        let first = password("1-3 a: abcde");
+        let neither = password("1-3 a: bbcde");
+
+        // act
+        // assert
+        assert!(!ExactlyOne.check(&both) && ExactlyOne.check(&first) && !ExactlyOne.check(&neither));
+        assert!(AtLeastOne.check(&both) && AtLeastOne.check(&first) && !AtLeastOne.check(&neither));
+        assert!(!NoneOf.check(&both) && !NoneOf.check(&first) && NoneOf.check(&neither));
+    }
+
+    #[test]
+    fn parse_should_combine_rules() {
+        // arrange
+        let text = "range and not (regex:'^a' or min-distinct:5)";
+
+        // act
+        let policy = parse(text).unwrap();
+
+        // assert
+        assert_eq!("(range and not (regex:'^a' or min-distinct:5))", policy.to_string());
+        assert!(policy.check(&password("1-3 a: baab")));
+        assert!(!policy.check(&password("1-3 a: abab")));
+        assert!(!policy.check(&password("1-3 a: bcdea")));
+        assert!(!policy.check(&password("1-3 a: bbbb")));
+    }
+
+    #[test]
+    fn reason_should_explain_verdict() {
+        // arrange
+        let too_many = password("2-8 c: ccccccccc");
+        let both = password("2-9 c: ccccccccc");
+
+        // act
+        // assert
+        assert_eq!("letter 'c' appears 9 times, allowed 2-8", Range.reason(&too_many));
+        assert_eq!("positions 2 and 9 both contain 'c'", ExactlyOne.reason(&both));
+        assert_eq!("has 1 distinct character, needs at least 2", MinDistinct(2).reason(&both));
+        assert_eq!("has 5 distinct characters, needs at least 2", MinDistinct(2).reason(&password("1-3 a: abcde")));
+    }
+
+    #[test]
+    fn reason_should_pick_deciding_sides() {
+        // arrange
+        let policy = parse("range and exactly-one").unwrap();
+        let password = password("2-9 c: ccccccccc");
+
+        // act
+        let reason = policy.reason(&password);
+
+        // assert
+        assert_eq!("positions 2 and 9 both contain 'c'", reason);
+    }
+
+    #[test]
+    fn parse_should_bind_and_before_or() {
+        // arrange
+        let text = "none-of or range and exactly-one";
+
+        // act
+        let policy = parse(text).unwrap();
+
+        // assert
+        assert_eq!("(none-of or (range and exactly-one))", policy.to_string());
+    }
+
+    #[test]
+    fn parse_should_describe_mistakes() {
+        // arrange
+        let cases = [
+            ("", "'Policy' parameter expected a rule at the end"),
+            ("range and", "'Policy' parameter expected a rule at the end"),
+            ("(range", "'Policy' parameter is missing a ')'"),
+            ("range range", "'Policy' parameter has 'range' after the end"),
+            ("ranges", "'Policy' parameter has unknown rule 'ranges'"),
+            ("min-distinct:x", "'Policy' parameter needs a number for min-distinct, got 'x'"),
+            ("regex:'a", "'Policy' parameter has a regex with no closing quote"),
+        ];
+
+        for (text, expected) in cases.iter() {
+            // act
+            let result = parse(text);
+
+            // assert
+            assert_eq!(*expected, result.err().unwrap().to_string());
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt;
+use std::fs;
+
+use common::Error;
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::policy::Policy;
+use crate::Password;
+
+/// Named rules loaded from a policy file, all of which a password must pass.
+///
+/// ```toml
+/// [[rule]]
+/// name = "Long enough"
+/// length = { min = 12 }
+///
+/// [[rule]]
+/// name = "Two digits"
+/// count = { class = "digit", min = 2 }
+///
+/// [[rule]]
+/// name = "Starts with a letter"
+/// position = { at = 1, class = "letter" }
+///
+/// [[rule]]
+/// name = "No dictionary words"
+/// forbidden = ["password", "qwerty"]
+/// ```
+pub struct RuleSet {
+    source: String,
+    rules: Vec<Rule>,
+}
+
+pub struct Rule {
+    pub name: String,
+    check: Check,
+}
+
+enum Check {
+    /// Between `min` and `max` graphemes of the class.
+    Count { class: Class, bounds: Bounds },
+    /// The grapheme at the 1-based position is of the class.
+    Position { at: usize, class: Class },
+    /// Between `min` and `max` graphemes long.
+    Length(Bounds),
+    /// None of the words appear, ignoring case.
+    Forbidden(Vec<String>),
+}
+
+enum Class {
+    Digit,
+    Upper,
+    Lower,
+    Letter,
+    Symbol,
+    Whitespace,
+    /// Any of the given graphemes.
+    Chars(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Bounds {
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default)]
+    rule: Vec<Spec>,
+}
+
+/// A rule as written in the file, with exactly one of its checks given.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Spec {
+    name: String,
+    count: Option<ClassSpec>,
+    position: Option<ClassSpec>,
+    length: Option<Bounds>,
+    forbidden: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClassSpec {
+    class: Option<String>,
+    chars: Option<String>,
+    at: Option<usize>,
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+impl RuleSet {
+    pub fn load(filename: &str) -> Result<RuleSet, Error> {
+        let text = fs::read_to_string(filename).map_err(|e| Error::io(filename, e))?;
+
+        RuleSet::parse(&text, filename)
+            .map_err(|message| Error::invalid_argument("Rules", &format!("in {} {}", filename, message)))
+    }
+
+    fn parse(text: &str, source: &str) -> Result<RuleSet, String> {
+        let file: File = toml::from_str(text).map_err(|e| format!("could not be read: {}", e))?;
+
+        if file.rule.is_empty() {
+            return Err(String::from("has no [[rule]] tables"));
+        }
+
+        let rules = file.rule.into_iter()
+            .enumerate()
+            .map(|(index, spec)| {
+                let line = line_of(text, index, spec.key());
+                Rule::new(spec).map_err(|message| match line {
+                    Some(line) => format!("on line {} {}", line, message),
+                    None => message,
+                })
+            })
+            .collect::<Result<Vec<Rule>, String>>()?;
+
+        Ok(RuleSet { source: String::from(source), rules })
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
+
+impl Rule {
+    fn new(spec: Spec) -> Result<Rule, String> {
+        let name = spec.name;
+        let invalid = |message: &str| format!("has rule '{}' {}", name, message);
+
+        let check = match (spec.count, spec.position, spec.length, spec.forbidden) {
+            (Some(count), None, None, None) => {
+                if count.at.is_some() {
+                    return Err(invalid("with 'at', which only applies to position"));
+                }
+                let class = Class::new(count.class, count.chars).map_err(|m| invalid(&m))?;
+                let bounds = Bounds { min: count.min, max: count.max };
+                bounds.validate().map_err(|m| invalid(&m))?;
+                Check::Count { class, bounds }
+            },
+            (None, Some(position), None, None) => {
+                if position.min.is_some() || position.max.is_some() {
+                    return Err(invalid("with 'min' or 'max', which only apply to count"));
+                }
+                let class = Class::new(position.class, position.chars).map_err(|m| invalid(&m))?;
+                match position.at {
+                    Some(at) if at > 0 => Check::Position { at, class },
+                    _ => return Err(invalid("without a 1-based 'at' position")),
+                }
+            },
+            (None, None, Some(length), None) => {
+                length.validate().map_err(|m| invalid(&m))?;
+                Check::Length(length)
+            },
+            (None, None, None, Some(words)) => {
+                if words.iter().any(|w| w.is_empty()) {
+                    return Err(invalid("with an empty forbidden word, which every password contains"));
+                }
+                Check::Forbidden(words.iter().map(|w| w.to_lowercase()).collect())
+            },
+            (None, None, None, None) => return Err(invalid("with none of count, position, length or forbidden")),
+            _ => return Err(invalid("with more than one of count, position, length or forbidden")),
+        };
+
+        Ok(Rule { name, check })
+    }
+
+    pub fn check(&self, value: &str) -> bool {
+        match &self.check {
+            Check::Count { class, bounds } => bounds.contains(class.count(value)),
+            Check::Position { at, class } => value.graphemes(true).nth(at - 1).is_some_and(|g| class.contains(g)),
+            Check::Length(bounds) => bounds.contains(value.graphemes(true).count()),
+            Check::Forbidden(words) => {
+                let value = value.to_lowercase();
+                !words.iter().any(|w| value.contains(w.as_str()))
+            },
+        }
+    }
+
+    pub fn reason(&self, value: &str) -> String {
+        match &self.check {
+            Check::Count { class, bounds } =>
+                format!("has {} {} characters, allowed {}", class.count(value), class, bounds),
+            Check::Position { at, class } => match value.graphemes(true).nth(at - 1) {
+                Some(g) => format!("position {} is '{}', needs {}", at, g, class),
+                None => format!("has no position {}, needs {}", at, class),
+            },
+            Check::Length(bounds) =>
+                format!("is {} characters long, allowed {}", value.graphemes(true).count(), bounds),
+            Check::Forbidden(words) => {
+                let lower = value.to_lowercase();
+                match words.iter().find(|w| lower.contains(w.as_str())) {
+                    Some(word) => format!("contains forbidden '{}'", word),
+                    None => format!("contains none of {} forbidden words", words.len()),
+                }
+            },
+        }
+    }
+}
+
+impl Class {
+    fn new(class: Option<String>, chars: Option<String>) -> Result<Class, String> {
+        match (class.as_deref(), chars) {
+            (None, Some(chars)) => Ok(Class::Chars(chars)),
+            (Some("digit"), None) => Ok(Class::Digit),
+            (Some("upper"), None) => Ok(Class::Upper),
+            (Some("lower"), None) => Ok(Class::Lower),
+            (Some("letter"), None) => Ok(Class::Letter),
+            (Some("symbol"), None) => Ok(Class::Symbol),
+            (Some("whitespace"), None) => Ok(Class::Whitespace),
+            (Some(other), None) => Err(format!("with unknown class '{}', expected digit, upper, lower, letter, symbol or whitespace", other)),
+            (None, None) => Err(String::from("without a 'class' or 'chars'")),
+            (Some(_), Some(_)) => Err(String::from("with both 'class' and 'chars'")),
+        }
+    }
+
+    /// Whether the grapheme is in the class, judged by its first character.
+    fn contains(&self, grapheme: &str) -> bool {
+        let c = match grapheme.chars().next() {
+            Some(c) => c,
+            None => return false,
+        };
+
+        match self {
+            Class::Digit => c.is_numeric(),
+            Class::Upper => c.is_uppercase(),
+            Class::Lower => c.is_lowercase(),
+            Class::Letter => c.is_alphabetic(),
+            Class::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+            Class::Whitespace => c.is_whitespace(),
+            Class::Chars(chars) => chars.graphemes(true).any(|g| g == grapheme),
+        }
+    }
+
+    fn count(&self, value: &str) -> usize {
+        value.graphemes(true).filter(|g| self.contains(g)).count()
+    }
+}
+
+impl Spec {
+    /// The key of the check given, if only one is.
+    fn key(&self) -> Option<&'static str> {
+        match (&self.count, &self.position, &self.length, &self.forbidden) {
+            (Some(_), None, None, None) => Some("count"),
+            (None, Some(_), None, None) => Some("position"),
+            (None, None, Some(_), None) => Some("length"),
+            (None, None, None, Some(_)) => Some("forbidden"),
+            _ => None,
+        }
+    }
+}
+
+/// The 1-based line of the `index`th `[[rule]]` table in `text`, or of its
+/// `key` when that starts a line of the table, as toml keeps no positions.
+fn line_of(text: &str, index: usize, key: Option<&str>) -> Option<usize> {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.iter().enumerate()
+        .filter(|(_, line)| line.trim() == "[[rule]]")
+        .map(|(number, _)| number)
+        .nth(index)?;
+    let end = lines[start + 1..].iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+
+    let key_line = key.and_then(|key| (start..end).find(|&number| {
+        lines[number].trim_start().strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='))
+    }));
+
+    Some(key_line.unwrap_or(start) + 1)
+}
+
+impl Bounds {
+    fn validate(&self) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => Err(format!("with min {} above max {}", min, max)),
+            _ => Ok(()),
+        }
+    }
+
+    fn contains(&self, n: usize) -> bool {
+        self.min.is_none_or(|min| n >= min) && self.max.is_none_or(|max| n <= max)
+    }
+}
+
+impl Policy for RuleSet {
+    fn check(&self, password: &Password) -> bool {
+        self.rules.iter().all(|rule| rule.check(&password.value))
+    }
+
+    /// Gives the reasons of the rules that fail.
+    fn reason(&self, password: &Password) -> String {
+        let failures: Vec<String> = self.rules.iter()
+            .filter(|rule| !rule.check(&password.value))
+            .map(|rule| format!("{}: {}", rule.name, rule.reason(&password.value)))
+            .collect();
+
+        if failures.is_empty() {
+            format!("passes all {} rules", self.rules.len())
+        } else {
+            failures.join("; ")
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rules from {}", self.source)
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Digit => write!(f, "digit"),
+            Class::Upper => write!(f, "upper case"),
+            Class::Lower => write!(f, "lower case"),
+            Class::Letter => write!(f, "letter"),
+            Class::Symbol => write!(f, "symbol"),
+            Class::Whitespace => write!(f, "whitespace"),
+            Class::Chars(chars) => write!(f, "one of '{}'", chars),
+        }
+    }
+}
+
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "{}-{}", min, max),
+            (Some(min), None) => write!(f, "at least {}", min),
+            (None, Some(max)) => write!(f, "at most {}", max),
+            (None, None) => write!(f, "any number"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        [[rule]]
+        name = "Long enough"
+        length = { min = 8, max = 20 }
+
+        [[rule]]
+        name = "Two digits"
+        count = { class = "digit", min = 2 }
+
+        [[rule]]
+        name = "Starts with a letter"
+        position = { at = 1, class = "letter" }
+
+        [[rule]]
+        name = "Has a symbol"
+        count = { chars = "!@#$", min = 1 }
+
+        [[rule]]
+        name = "No dictionary words"
+        forbidden = ["password", "qwerty"]
+    "#;
+
+    #[test]
+    fn check_should_need_every_rule() {
+        // arrange
+        let rules = RuleSet::parse(RULES, "rules.toml").unwrap();
+
+        // act
+        // assert
+        assert!(rules.check(&Password::plain(1, "hunter42!x")));
+        assert!(!rules.check(&Password::plain(2, "1hunter42!x")));
+        assert!(!rules.check(&Password::plain(3, "MyPassword12!")));
+    }
+
+    #[test]
+    fn reason_should_name_failing_rules() {
+        // arrange
+        let rules = RuleSet::parse(RULES, "rules.toml").unwrap();
+
+        // act
+        let reason = rules.reason(&Password::plain(1, "9Qwerty"));
+
+        // assert
+        assert_eq!("Long enough: is 7 characters long, allowed 8-20; \
+            Two digits: has 1 digit characters, allowed at least 2; \
+            Starts with a letter: position 1 is '9', needs letter; \
+            Has a symbol: has 0 one of '!@#$' characters, allowed at least 1; \
+            No dictionary words: contains forbidden 'qwerty'", reason);
+    }
+
+    #[test]
+    fn parse_should_describe_mistakes() {
+        // arrange
+        let cases = [
+            ("", "has no [[rule]] tables"),
+            ("[[rule]]\nname = \"a\"", "on line 1 has rule 'a' with none of count, position, length or forbidden"),
+            ("[[rule]]\nname = \"a\"\nlength = {}\nforbidden = []", "on line 1 has rule 'a' with more than one of count, position, length or forbidden"),
+            ("[[rule]]\nname = \"a\"\ncount = { class = \"emoji\" }", "on line 3 has rule 'a' with unknown class 'emoji', expected digit, upper, lower, letter, symbol or whitespace"),
+            ("[[rule]]\nname = \"a\"\nposition = { class = \"digit\" }", "on line 3 has rule 'a' without a 1-based 'at' position"),
+            ("[[rule]]\nname = \"a\"\nlength = { min = 1 }\n\n[[rule]]\nname = \"b\"\nforbidden = [\"x\", \"\"]", "on line 7 has rule 'b' with an empty forbidden word, which every password contains"),
+            ("[[rule]]\nname = \"a\"\nlength = { min = 12, max = 8 }", "on line 3 has rule 'a' with min 12 above max 8"),
+            ("[[rule]]\nname = \"a\"\ncount = { class = \"digit\", min = 3, max = 2 }", "on line 3 has rule 'a' with min 3 above max 2"),
+        ];
+
+        for (text, expected) in cases.iter() {
+            // act
+            let result = RuleSet::parse(text, "rules.toml");
+
+            // assert
+            assert_eq!(*expected, result.err().unwrap());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use common::{Answer, Error};
+
+use crate::config::Config;
+use crate::policy::{self, Policy};
+use crate::Password;
+
+/// Characters in the longest histogram bar.
+const BAR_WIDTH: usize = 40;
+
+/// Summarises the passwords as text tables: how often each letter is given
+/// and how many of its passwords pass, histograms of range widths and
+/// password lengths, pass rates per policy, and which passwords the part 1
+/// and part 2 policies disagree on.
+pub fn report(passwords: &[Password], config: &Config) -> Result<Answer, Error> {
+    if passwords.is_empty() {
+        return Err(Error::NoSolution("No passwords to summarise"));
+    }
+
+    // Both parts' policies, then the chosen one if it is neither
+    let mut policies: Vec<&dyn Policy> = vec![&policy::Range, &policy::ExactlyOne];
+    let chosen = config.policy();
+    if policies.iter().all(|p| p.to_string() != chosen.to_string()) {
+        policies.push(chosen);
+    }
+
+    let verdicts: Vec<Vec<bool>> = passwords.iter()
+        .map(|password| policies.iter().map(|policy| policy.check(password)).collect())
+        .collect();
+
+    let sections = [
+        ("Letters", letters(passwords, &policies, &verdicts)),
+        ("Range widths", histogram("Width", passwords.iter().map(|p| p.max - p.min))),
+        ("Lengths", histogram("Length", passwords.iter().map(|p| p.length()))),
+        ("Policies", pass_rates(&policies, &verdicts)),
+        ("Part 1 vs part 2", comparison(passwords, &verdicts)),
+    ];
+
+    let text: Vec<String> = sections.iter()
+        .map(|(title, table)| format!("{}\n\n{}", title, table))
+        .collect();
+
+    Ok(Answer::new(text.join("\n\n")).with("Passwords", passwords.len() as i64))
+}
+
+/// How many passwords give each letter, most common first, with the share of
+/// them each policy passes.
+fn letters(passwords: &[Password], policies: &[&dyn Policy], verdicts: &[Vec<bool>]) -> String {
+    let mut counts: BTreeMap<&str, (usize, Vec<usize>)> = BTreeMap::new();
+
+    for (password, verdicts) in passwords.iter().zip(verdicts) {
+        let (count, passed) = counts.entry(password.letter.as_ref())
+            .or_insert_with(|| (0, vec![0; policies.len()]));
+
+        *count += 1;
+        for (passed, &valid) in passed.iter_mut().zip(verdicts) {
+            if valid {
+                *passed += 1;
+            }
+        }
+    }
+
+    let mut counts: Vec<(&str, (usize, Vec<usize>))> = counts.into_iter().collect();
+    counts.sort_by(|(_, (a, _)), (_, (b, _))| b.cmp(a));
+
+    let names: Vec<String> = policies.iter().map(|p| p.to_string()).collect();
+    let mut headers = vec!["Letter", "Passwords"];
+    headers.extend(names.iter().map(|n| n.as_str()));
+
+    let rows: Vec<Vec<String>> = counts.iter()
+        .map(|(letter, (count, passed))| {
+            let mut row = vec![letter.to_string(), count.to_string()];
+            row.extend(passed.iter().map(|&passed| rate(passed, *count)));
+            row
+        })
+        .collect();
+
+    common::table(&headers, &rows)
+}
+
+/// How many passwords have each value, with a bar scaled to the most common.
+fn histogram<I: Iterator<Item = usize>>(label: &str, values: I) -> String {
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+
+    let most = counts.values().copied().max().unwrap_or(0);
+    let rows: Vec<Vec<String>> = counts.iter()
+        .map(|(value, &count)| vec![value.to_string(), count.to_string(), bar(count, most)])
+        .collect();
+
+    common::table(&[label, "Passwords", ""], &rows)
+}
+
+fn pass_rates(policies: &[&dyn Policy], verdicts: &[Vec<bool>]) -> String {
+    let rows: Vec<Vec<String>> = policies.iter().enumerate()
+        .map(|(index, policy)| {
+            let valid = verdicts.iter().filter(|v| v[index]).count();
+            let invalid = verdicts.len() - valid;
+            vec![policy.to_string(), valid.to_string(), invalid.to_string(), rate(valid, verdicts.len())]
+        })
+        .collect();
+
+    common::table(&["Policy", "Valid", "Invalid", "Pass rate"], &rows)
+}
+
+/// Counts each pairing of part 1 and part 2 verdicts, listing the lines of
+/// passwords only one part accepts.
+fn comparison(passwords: &[Password], verdicts: &[Vec<bool>]) -> String {
+    let pairings = [
+        ("Both valid", true, true),
+        ("Part 1 only", true, false),
+        ("Part 2 only", false, true),
+        ("Neither", false, false),
+    ];
+
+    let rows: Vec<Vec<String>> = pairings.iter()
+        .map(|&(label, part1, part2)| {
+            let lines: Vec<String> = passwords.iter().zip(verdicts)
+                .filter(|(_, v)| v[0] == part1 && v[1] == part2)
+                .map(|(password, _)| password.line.to_string())
+                .collect();
+
+            // Lines only matter where the parts disagree
+            let shown = if part1 == part2 { String::new() } else { line_list(&lines) };
+            vec![String::from(label), lines.len().to_string(), rate(lines.len(), passwords.len()), shown]
+        })
+        .collect();
+
+    common::table(&["Verdicts", "Passwords", "Share", "Lines"], &rows)
+}
+
+/// Lists up to ten line numbers, saying how many more there are.
+fn line_list(lines: &[String]) -> String {
+    const SHOWN: usize = 10;
+
+    if lines.len() <= SHOWN {
+        return lines.join(", ");
+    }
+
+    format!("{}, ... {} more", lines[..SHOWN].join(", "), lines.len() - SHOWN)
+}
+
+fn rate(count: usize, total: usize) -> String {
+    format!("{:.1}%", 100.0 * count as f64 / total as f64)
+}
+
+fn bar(count: usize, most: usize) -> String {
+    "#".repeat((count * BAR_WIDTH).div_ceil(most))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Args, Value};
+
+    fn passwords(text: &str) -> Vec<Password<'static>> {
+        text.lines()
+            .enumerate()
+            .map(|(index, line)| Password { line: index + 1, ..Password::new(String::from(line)).unwrap() })
+            .collect()
+    }
+
+    fn config(args: &[&str]) -> Config {
+        let args = args.iter().map(|a| String::from(*a)).collect();
+        Config::new(&Args::parse(&crate::config::PARAMS, args).unwrap()).unwrap()
+    }
+
+    fn section<'a>(text: &'a str, title: &str) -> &'a str {
+        let start = text.find(&format!("{}\n\n", title)).unwrap() + title.len() + 2;
+        let end = text[start..].find("\n\n").map_or(text.len(), |end| start + end);
+        &text[start..end]
+    }
+
+    #[test]
+    fn report_should_compare_parts() {
+        // arrange
+        let passwords = passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 a: aab");
+        let config = config(&["-", "1", "--stats"]);
+
+        // act
+        let answer = report(&passwords, &config).unwrap();
+
+        // assert
+        let text = match &answer.value {
+            Value::Text(text) => text.clone(),
+            other => panic!("expected text, got {:?}", other),
+        };
+        let expected = "\
+            Verdicts    | Passwords | Share | Lines\n\
+            ------------+-----------+-------+------\n\
+            Both valid  | 1         | 25.0% |\n\
+            Part 1 only | 2         | 50.0% | 3, 4\n\
+            Part 2 only | 0         | 0.0%  |\n\
+            Neither     | 1         | 25.0% |";
+        assert_eq!(expected, section(&text, "Part 1 vs part 2"));
+        assert_eq!(Some(&Value::Integer(4)), answer.detail("Passwords"));
+    }
+
+    #[test]
+    fn report_should_count_letters_and_widths() {
+        // arrange
+        let passwords = passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 a: aab");
+        let config = config(&["-", "1", "--stats", "--policy=min-distinct:3"]);
+
+        // act
+        let answer = report(&passwords, &config).unwrap();
+
+        // assert
+        let text = match &answer.value {
+            Value::Text(text) => text.clone(),
+            other => panic!("expected text, got {:?}", other),
+        };
+        let letters = "\
+            Letter | Passwords | range  | exactly-one | min-distinct:3\n\
+            -------+-----------+--------+-------------+---------------\n\
+            a      | 2         | 100.0% | 50.0%       | 50.0%\n\
+            b      | 1         | 0.0%   | 0.0%        | 100.0%\n\
+            c      | 1         | 100.0% | 0.0%        | 0.0%";
+        let widths = "\
+            Width | Passwords |\n\
+            ------+-----------+-----------------------------------------\n\
+            1     | 1         | ####################\n\
+            2     | 2         | ########################################\n\
+            7     | 1         | ####################";
+        assert_eq!(letters, section(&text, "Letters"));
+        assert_eq!(widths, section(&text, "Range widths"));
+    }
+}
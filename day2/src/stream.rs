@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::mem;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use common::{Answer, Diagnostics, Error};
+
+use crate::config::Config;
+use crate::{parser, Password};
+
+/// Bytes of input handed to a thread at a time, rounded up to a whole line.
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// How many passwords passed the config's policy and each rule of its rules
+/// file, which is all a count needs to keep of them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub checked: usize,
+    pub valid: usize,
+    /// Passwords that passed each rule of the rules file, in order.
+    pub passed: Vec<usize>,
+}
+
+impl Tally {
+    pub fn new(config: &Config) -> Tally {
+        let rules = config.rules.as_ref().map_or(0, |rules| rules.rules().len());
+        Tally { checked: 0, valid: 0, passed: vec![0; rules] }
+    }
+
+    pub fn add(&mut self, password: &Password, config: &Config) {
+        self.checked += 1;
+
+        let valid = match &config.rules {
+            // Valid when every rule passes, so check each once
+            Some(rules) => {
+                let mut valid = true;
+                for (passed, rule) in self.passed.iter_mut().zip(rules.rules()) {
+                    if rule.check(&password.value) {
+                        *passed += 1;
+                    } else {
+                        valid = false;
+                    }
+                }
+                valid
+            },
+            None => config.policy().check(password),
+        };
+
+        if valid {
+            self.valid += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Tally) {
+        self.checked += other.checked;
+        self.valid += other.valid;
+
+        for (passed, other) in self.passed.iter_mut().zip(&other.passed) {
+            *passed += other;
+        }
+    }
+
+    /// The count of valid passwords, with how many passed and failed each rule.
+    pub fn answer(&self, config: &Config) -> Answer {
+        let mut answer = Answer::new(self.valid as i64).with("Policy", config.policy().to_string());
+
+        if let Some(rules) = &config.rules {
+            for (rule, passed) in rules.rules().iter().zip(&self.passed) {
+                answer = answer.with(&rule.name, format!("{} passed, {} failed", passed, self.checked - passed));
+            }
+        }
+
+        answer
+    }
+}
+
+/// Checks every line of `reader` against the config's policy without keeping
+/// any of them, on `config.threads` threads. Lines that fail to parse are
+/// reported to `diagnostics` in input order, whatever thread checked them.
+pub fn count<R: BufRead>(reader: R, name: &str, config: &Config, diagnostics: &mut Diagnostics) -> Result<Tally, Error> {
+    if config.threads > 1 {
+        chunked(reader, name, config, CHUNK_BYTES, diagnostics)
+    } else {
+        sequential(reader, name, config, diagnostics)
+    }
+}
+
+/// Reads a line at a time into a single reused buffer.
+fn sequential<R: BufRead>(mut reader: R, name: &str, config: &Config, diagnostics: &mut Diagnostics) -> Result<Tally, Error> {
+    let mut tally = Tally::new(config);
+    let mut line = String::new();
+    let mut number = 0;
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(|e| Error::io(name, e))? == 0 {
+            return Ok(tally);
+        }
+        number += 1;
+
+        match parse(trim_newline(&line), number, config) {
+            Ok(Some(password)) => tally.add(&password, config),
+            Ok(None) => (),
+            Err(err) => diagnostics.report(err.in_file(name))?,
+        }
+    }
+}
+
+/// Lines read into one buffer, to be checked on a worker thread.
+struct Chunk {
+    index: usize,
+    /// 1-based line number of the first line in `text`.
+    first: usize,
+    text: String,
+}
+
+/// What a worker found in a chunk, handing its buffer back to be refilled.
+struct Done {
+    index: usize,
+    tally: Tally,
+    errors: Vec<Error>,
+    text: String,
+}
+
+/// Reads chunks of about `chunk_bytes` on the calling thread and checks them
+/// on worker threads. At most two buffers per thread are ever allocated, so
+/// memory stays bounded however long the input is.
+fn chunked<R: BufRead>(mut reader: R, name: &str, config: &Config, chunk_bytes: usize, diagnostics: &mut Diagnostics) -> Result<Tally, Error> {
+    let threads = config.threads;
+    let (work, jobs) = mpsc::sync_channel::<Chunk>(threads);
+    let jobs = Mutex::new(jobs);
+
+    thread::scope(|scope| {
+        // Owned by the closure, so workers stop however it returns
+        let work = work;
+        let (finished, done) = mpsc::channel::<Done>();
+
+        for _ in 0..threads {
+            let (jobs, finished) = (&jobs, finished.clone());
+
+            scope.spawn(move || loop {
+                // Hold the lock only while waiting, not while checking
+                let chunk = match jobs.lock().unwrap().recv() {
+                    Ok(chunk) => chunk,
+                    Err(_) => return,
+                };
+
+                if finished.send(check(chunk, name, config)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(finished);
+
+        let mut results = InOrder::new(config);
+        let mut buffers = 0;
+        let mut index = 0;
+        let mut first = 1;
+
+        loop {
+            let mut text = if buffers < threads * 2 {
+                buffers += 1;
+                String::with_capacity(chunk_bytes)
+            } else {
+                match done.recv() {
+                    Ok(finished) => results.add(finished, diagnostics)?,
+                    // Every worker has stopped, which only a panic does
+                    Err(_) => break,
+                }
+            };
+
+            text.clear();
+            let lines = fill(&mut reader, &mut text, chunk_bytes).map_err(|e| Error::io(name, e))?;
+            if lines == 0 {
+                break;
+            }
+
+            if work.send(Chunk { index, first, text }).is_err() {
+                break;
+            }
+            index += 1;
+            first += lines;
+        }
+
+        // Let the workers stop once the last chunks are checked
+        drop(work);
+        for finished in done {
+            results.add(finished, diagnostics)?;
+        }
+
+        Ok(results.tally)
+    })
+}
+
+/// Appends whole lines to `text` until it holds at least `chunk_bytes`,
+/// returning how many lines were read.
+fn fill<R: BufRead>(reader: &mut R, text: &mut String, chunk_bytes: usize) -> io::Result<usize> {
+    let mut lines = 0;
+
+    while text.len() < chunk_bytes {
+        if reader.read_line(text)? == 0 {
+            break;
+        }
+        lines += 1;
+    }
+
+    Ok(lines)
+}
+
+fn check(chunk: Chunk, name: &str, config: &Config) -> Done {
+    let mut tally = Tally::new(config);
+    let mut errors: Vec<Error> = Vec::new();
+
+    for (offset, line) in chunk.text.lines().enumerate() {
+        match parse(line, chunk.first + offset, config) {
+            Ok(Some(password)) => tally.add(&password, config),
+            Ok(None) => (),
+            Err(err) => errors.push(err.in_file(name)),
+        }
+    }
+
+    Done { index: chunk.index, tally, errors, text: chunk.text }
+}
+
+/// Merges finished chunks in input order, so parse errors are reported in
+/// the order a single thread would report them.
+struct InOrder {
+    next: usize,
+    pending: BTreeMap<usize, (Tally, Vec<Error>)>,
+    tally: Tally,
+}
+
+impl InOrder {
+    fn new(config: &Config) -> InOrder {
+        InOrder { next: 0, pending: BTreeMap::new(), tally: Tally::new(config) }
+    }
+
+    /// Takes a finished chunk, returning its buffer for reuse.
+    fn add(&mut self, mut finished: Done, diagnostics: &mut Diagnostics) -> Result<String, Error> {
+        let text = mem::take(&mut finished.text);
+        self.pending.insert(finished.index, (finished.tally, finished.errors));
+
+        while let Some((tally, errors)) = self.pending.remove(&self.next) {
+            self.tally.merge(&tally);
+            for err in errors {
+                diagnostics.report(err)?;
+            }
+            self.next += 1;
+        }
+
+        Ok(text)
+    }
+}
+
+/// Parses a line borrowing from it, or `None` for a blank line of plain
+/// passwords.
+fn parse<'a>(line: &'a str, number: usize, config: &Config) -> Result<Option<Password<'a>>, Error> {
+    // Rules from a file only look at the password, so lines hold nothing else
+    if config.rules.is_some() {
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(Password::plain(number, line)));
+    }
+
+    match parser::password(line) {
+        Ok(password) => Ok(Some(Password { line: number, ..password })),
+        Err(err) => Err(err.on_line(number)),
+    }
+}
+
+fn trim_newline(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Args, Input, Strictness};
+
+    fn config(args: &[&str]) -> Config {
+        let args = args.iter().map(|a| String::from(*a)).collect();
+        Config::new(&Args::parse(&crate::config::PARAMS, args).unwrap()).unwrap()
+    }
+
+    const LINES: &str = "1-3 a: abcde\r\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-1 x: x";
+
+    #[test]
+    fn count_should_match_reading_every_line() {
+        // arrange
+        let config = config(&["-", "1", "--stream"]);
+        let reader = Input::text(LINES).reader().unwrap();
+
+        // act
+        let tally = count(reader, "<text>", &config, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(4, tally.checked);
+        assert_eq!(3, tally.valid);
+    }
+
+    #[test]
+    fn chunked_should_match_sequential() {
+        // arrange
+        let config = config(&["-", "2", "--threads=3"]);
+        let text = format!("{}\n", LINES).repeat(50);
+
+        // act
+        let expected = sequential(text.as_bytes(), "<text>", &config, &mut Diagnostics::default()).unwrap();
+        let tally = chunked(text.as_bytes(), "<text>", &config, 40, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(200, expected.checked);
+        assert_eq!(expected, tally);
+    }
+
+    #[test]
+    fn chunked_should_report_errors_in_line_order() {
+        // arrange
+        let config = config(&["-", "1", "--threads=2"]);
+        let text = "1-3 a: abcde\n\n1-3 b: cdefg\n1-3 b\n2-9 c: ccccccccc\n0-1 a: a\n";
+        let mut diagnostics = Diagnostics::new(Strictness::Lenient);
+
+        // act
+        let tally = chunked(text.as_bytes(), "pw.txt", &config, 1, &mut diagnostics).unwrap();
+
+        // assert
+        let lines: Vec<usize> = diagnostics.warnings().iter().map(|w| w.line).collect();
+        assert_eq!(vec![2, 4, 6], lines);
+        assert_eq!(3, tally.checked);
+    }
+
+    #[test]
+    fn chunked_should_stop_at_first_error_when_strict() {
+        // arrange
+        let config = config(&["-", "1", "--threads=2"]);
+        let text = "1-3 a: abcde\n1-3 b\n0-1 a: a\n";
+
+        // act
+        let result = chunked(text.as_bytes(), "pw.txt", &config, 1, &mut Diagnostics::new(Strictness::Strict));
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!(2, diagnostic.line),
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
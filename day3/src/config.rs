@@ -1,62 +1,84 @@
+use common::{Args, Error, Input, Kind, Param};
+
+use crate::slope::Slope;
+
 pub struct Config {
     pub filename: String,
     pub slopes: Vec<Slope>,
+    /// Whether to draw the map with each slope's path rather than count trees.
+    pub render: bool,
+    /// Whether to draw each path in its own colour.
+    pub colour: bool,
 }
 
-pub struct Slope {
-    pub x_move: usize,
-    pub y_move: usize,
-}
+pub static PARAMS: [Param; 6] = [
+    Param { name: "input", label: "Filename", value: "FILE", help: "Map of the slope, or `-` to read standard input", kind: Kind::Value },
+    Param { name: "slope", label: "Slope", value: "X,Y", help: "Moves right and down per step, negative for left or up and fractions such as 1/2 allowed; repeat to multiply the results", kind: Kind::Multiple },
+    Param { name: "render", label: "Render", value: "", help: "Draw the map with each path on it, O for a tree hit and X for an open square", kind: Kind::Flag },
+    Param { name: "colour", label: "Colour", value: "", help: "Draw each slope's path in its own colour, implies --render", kind: Kind::Flag },
+    Param { name: "part1", label: "Part 1", value: "", help: "Preset: slope 3,1 on day3/data.txt", kind: Kind::Flag },
+    Param { name: "part2", label: "Part 2", value: "", help: "Preset: slopes 1,1 3,1 5,1 7,1 1,2 on day3/data.txt", kind: Kind::Flag },
+];
 
 impl Config {
-    pub fn new(args: Vec<String>) -> Result<Config, &'static str> {
-        let mut args = args.into_iter();
-
-        let filename = match args.next() {
-            Some(arg) => {
-                // Check for default arguments and short circuit if matched
-                match arg.as_str() {
-                    "--part1" => return Ok(Config {
-                            filename: String::from("day3/data.txt"),
-                            slopes: vec![
-                                Slope{ x_move: 3, y_move: 1},],
-                        }),
-                    "--part2" => return Ok(Config {
-                            filename: String::from("day3/data.txt"),
-                            slopes: vec![
-                                Slope{ x_move: 1, y_move: 1},
-                                Slope{ x_move: 3, y_move: 1},
-                                Slope{ x_move: 5, y_move: 1},
-                                Slope{ x_move: 7, y_move: 1},
-                                Slope{ x_move: 1, y_move: 2},],
-                        }),
-                    _ => arg,
-                }
-            },
-            None => return Err("'Filename' parameter not supplied"),
-        };
+    pub fn new(args: &Args) -> Result<Config, Error> {
+        let colour = args.flag("colour");
+        let render = args.flag("render") || colour;
+
+        // Check for default arguments and short circuit if matched
+        if args.flag("part1") {
+            return Ok(Config { filename: preset_filename()?, render, colour, ..Config::part1() });
+        } else if args.flag("part2") {
+            return Ok(Config { filename: preset_filename()?, render, colour, ..Config::part2() });
+        }
+
+        let filename = String::from(args.require("input")?);
 
         let mut slopes: Vec<Slope> = Vec::new();
-        for arg in args {
-            let pair: Vec<&str> = arg.split(',').collect();
-
-            if pair.len() < 2 || pair.len() > 2 {
-                return Err("Invalid slope pair, use format '{X},{Y}'");
-            }
-    
-            let x_move: usize = match pair[0].parse() {
-                Ok(p) => p,
-                Err(_) => return Err("'X-Move' parameter must be an integer"),
-            };
-    
-            let y_move: usize = match pair[1].parse() {
-                Ok(p) => p,
-                Err(_) => return Err("'Y-Move' parameter must be an integer"),
-            };
-
-            slopes.push(Slope { x_move, y_move });
+        for arg in args.all("slope") {
+            slopes.push(Slope::parse(arg)?);
+        }
+
+        Ok(Config{ filename, slopes, render, colour })
+    }
+
+    pub fn part1() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            slopes: vec![
+                Slope::new(3, 1),],
+            render: false,
+            colour: false,
         }
+    }
+
+    pub fn part2() -> Config {
+        Config {
+            filename: String::from(INPUT),
+            slopes: vec![
+                Slope::new(1, 1),
+                Slope::new(3, 1),
+                Slope::new(5, 1),
+                Slope::new(7, 1),
+                Slope::new(1, 2),],
+            render: false,
+            colour: false,
+        }
+    }
+}
+
+pub const INPUT: &str = "day3/data.txt";
+
+/// Locates the preset input, which need not be relative to the current
+/// directory.
+pub fn input() -> Result<Input, Error> {
+    let path = common::resolve("day3", "data.txt", env!("CARGO_MANIFEST_DIR"))?;
+    Ok(Input::Path(path))
+}
 
-        return Ok(Config{ filename, slopes });
+fn preset_filename() -> Result<String, Error> {
+    match input()? {
+        Input::Path(path) => Ok(path.display().to_string()),
+        _ => Ok(String::from(INPUT)),
     }
 }
\ No newline at end of file
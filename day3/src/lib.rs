@@ -1,4 +1,4 @@
-use std::error::Error;
+use common::{Answer, Args, Diagnostics, Error, Input, Param, Puzzle};
 
 mod config;
 use config::Config;
@@ -6,57 +6,142 @@ use config::Config;
 mod pattern;
 use pattern::{Pattern, Tile};
 
-pub fn run(config: common::Config) -> Result<(), Box<dyn Error>> {
-    let config = Config::new(config.args)?;
+mod render;
+mod slope;
+use slope::Slope;
 
-    let pattern = read_pattern(&config)?;
+/// Most steps a path may take, as slopes moving a tiny part of a row per step
+/// would otherwise take practically forever to cross the map.
+const MAX_STEPS: i128 = 1_000_000;
 
-    let result = find_blocking_trees(&pattern, &config)?;
+pub struct Day3;
 
-    println!("Blocking trees {:?}", result);
-    
-    if result.len() > 1 {
-        let multiplication: i64 = result.into_iter().map(|x| i64::from(x)).product();
+pub static PUZZLE: Day3 = Day3;
 
-        println!("Multiplication {}", multiplication);
+pub fn register(registry: &mut common::Registry) {
+    registry.register(&PUZZLE);
+}
+
+impl Puzzle for Day3 {
+    type Data = Pattern;
+
+    fn name(&self) -> &'static str {
+        "day3"
+    }
+
+    fn about(&self) -> &'static str {
+        "Count trees hit while sledding down a slope"
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &config::PARAMS
+    }
+
+    fn input(&self) -> Result<Input, Error> {
+        config::input()
+    }
+
+    fn parse(&self, input: &Input, diagnostics: &mut Diagnostics) -> Result<Pattern, Error> {
+        read_pattern(input, diagnostics)
+    }
+
+    fn part1(&self, pattern: &Pattern) -> Result<Answer, Error> {
+        solve(pattern, &Config::part1())
+    }
+
+    fn part2(&self, pattern: &Pattern) -> Result<Answer, Error> {
+        solve(pattern, &Config::part2())
+    }
+
+    fn run(&self, args: &Args, diagnostics: &mut Diagnostics) -> Result<Answer, Error> {
+        let config = Config::new(args)?;
+        let pattern = read_pattern(&Input::from_arg(&config.filename), diagnostics)?;
+
+        solve(&pattern, &config)
     }
-    
-    Ok(())
 }
 
-fn read_pattern(config: &Config) -> Result<Pattern, Box<dyn Error>> {
-    let lines = common::read_lines(&config.filename)?;
-    let pattern = Pattern::new(lines)?;
+fn solve(pattern: &Pattern, config: &Config) -> Result<Answer, Error> {
+    let result = find_blocking_trees(pattern, config)?;
 
-    Ok(pattern)
+    if config.render {
+        let paths: Vec<Vec<(i128, usize)>> = config.slopes.iter()
+            .map(|slope| path(pattern, slope))
+            .collect::<Result<_, Error>>()?;
+        let map = render::map(pattern, &config.slopes, &paths, config.colour)?;
+
+        let mut answer = Answer::new(map);
+        for (slope, trees) in config.slopes.iter().zip(result) {
+            answer = answer.with(&slope.to_string(), trees);
+        }
+        return Ok(answer);
+    }
+
+    let multiplication: i64 = result.iter().copied().map(i64::from).product();
+
+    let mut answer = Answer::new(multiplication);
+    for (slope, trees) in config.slopes.iter().zip(result) {
+        answer = answer.with(&slope.to_string(), trees);
+    }
+
+    Ok(answer)
+}
+
+fn read_pattern(input: &Input, diagnostics: &mut Diagnostics) -> Result<Pattern, Error> {
+    Pattern::read(input.lines()?, Some(&input.name()), diagnostics)
 }
 
-fn find_blocking_trees(pattern: &Pattern, config: &Config) -> Result<Vec<i32>, &'static str> {
+fn find_blocking_trees(pattern: &Pattern, config: &Config) -> Result<Vec<i32>, Error> {
     let mut results: Vec<i32> = Vec::new();
 
     for slope in &config.slopes {
-        let mut x = 0;
-        let mut y = 0;
-        let mut count = 0;
-    
-        while y < pattern.height {
-            if let Tile::Tree = pattern.get_tile(x, y) {
-                count += 1;
-            }
-    
-            x += slope.x_move;
-            y += slope.y_move;
-        }
+        slope.validate()?;
+
+        let count = path(pattern, slope)?.into_iter()
+            .filter(|&(x, y)| matches!(pattern.get_tile(x, y), Tile::Tree))
+            .count();
 
-        results.push(count);
+        results.push(count as i32);
     }
 
     Ok(results)
 }
 
+/// The tiles the sled visits going down the slope, in order, as columns of
+/// the repeating pattern and rows. A tile several steps land on is given once.
+/// The slope must move up or down, and cross the map within `MAX_STEPS`.
+fn path(pattern: &Pattern, slope: &Slope) -> Result<Vec<(i128, usize)>, Error> {
+    let limit = slope.steps_across(pattern.height);
+    if limit > MAX_STEPS {
+        let message = format!("{} would take {} steps to cross the map, more than {}", slope, limit, MAX_STEPS);
+        return Err(Error::invalid_argument("Slope", &message));
+    }
+
+    let height = pattern.height as i128;
+    let start = if slope.is_upward() { height - 1 } else { 0 };
+    let mut tiles: Vec<(i128, usize)> = Vec::new();
+
+    for steps in 0..=limit as i64 {
+        let (x, y) = slope.position(steps);
+        let y = start + y;
+
+        if y < 0 || y >= height {
+            break;
+        }
+
+        // Both coordinates only ever move one way, so a tile landed on again
+        // is the one before
+        let tile = (x, y as usize);
+        if tiles.last() != Some(&tile) {
+            tiles.push(tile);
+        }
+    }
+
+    Ok(tiles)
+}
+
 #[cfg(test)]
 mod tests {
-    use config::Slope;
     use super::*;
 
     #[test]
@@ -67,13 +152,15 @@ mod tests {
             String::from(".#.."),
             String::from("...."),
             String::from("...#"),
-        ].into_iter().map(|t| Ok(t));
+        ].into_iter().map(Ok);
 
         let pattern = Pattern::new(text).unwrap();
 
         let config = Config{ 
             filename: String::from("_"),
-            slopes: vec![Slope {x_move: 1, y_move: 1 }],
+            slopes: vec![Slope::new(1, 1)],
+            render: false,
+            colour: false,
         };
 
         // act
@@ -91,13 +178,15 @@ mod tests {
             String::from("."),
             String::from("#"),
             String::from("."),
-        ].into_iter().map(|t| Ok(t));
+        ].into_iter().map(Ok);
 
         let pattern = Pattern::new(text).unwrap();
 
         let config = Config{ 
             filename: String::from("_"),
-            slopes: vec![Slope {x_move: 1, y_move: 1 }],
+            slopes: vec![Slope::new(1, 1)],
+            render: false,
+            colour: false,
         };
 
         // act
@@ -115,16 +204,18 @@ mod tests {
             String::from(".#.."),
             String::from(".##."),
             String::from("...#"),
-        ].into_iter().map(|t| Ok(t));
+        ].into_iter().map(Ok);
 
         let pattern = Pattern::new(text).unwrap();
 
         let config = Config{ 
             filename: String::from("_"),
             slopes: vec![
-                Slope {x_move: 1, y_move: 1 },
-                Slope {x_move: 1, y_move: 2 },
+                Slope::new(1, 1),
+                Slope::new(1, 2),
             ],
+            render: false,
+            colour: false,
         };
 
         // act
@@ -134,4 +225,173 @@ mod tests {
         assert_eq!(4, result[0]);
         assert_eq!(2, result[1]);
     }
+
+    #[test]
+    fn find_blocking_trees_should_follow_signed_and_fractional_slopes() {
+        // arrange
+        let text = vec![
+            String::from("#..."),
+            String::from("...#"),
+            String::from("..#."),
+            String::from(".#.#"),
+        ].into_iter().map(Ok);
+
+        let pattern = Pattern::new(text).unwrap();
+
+        let config = Config{
+            filename: String::from("_"),
+            slopes: vec![
+                Slope::parse("-1,1").unwrap(),
+                Slope::parse("1/2,1").unwrap(),
+                Slope::parse("1,-1").unwrap(),
+            ],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let result = find_blocking_trees(&pattern, &config).unwrap();
+
+        // assert
+        assert_eq!(vec![4, 2, 0], result);
+    }
+
+    #[test]
+    fn find_blocking_trees_should_count_each_tile_once() {
+        // arrange
+        let text = vec![
+            String::from("#.."),
+            String::from(".#."),
+            String::from("..#"),
+        ].into_iter().map(Ok);
+
+        let pattern = Pattern::new(text).unwrap();
+
+        let config = Config{
+            filename: String::from("_"),
+            slopes: vec![
+                Slope::parse("1/2,1/2").unwrap(),
+                Slope::parse("0,1/2").unwrap(),
+            ],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let result = find_blocking_trees(&pattern, &config).unwrap();
+
+        // assert
+        assert_eq!(vec![3, 1], result);
+    }
+
+    #[test]
+    fn find_blocking_trees_should_follow_extreme_slopes() {
+        // arrange
+        let text = vec![
+            String::from("#.."),
+            String::from(".#."),
+            String::from("..#"),
+        ].into_iter().map(Ok);
+
+        let pattern = Pattern::new(text).unwrap();
+
+        // Both are 1 more than a multiple of 3, so each step moves 1 tile
+        // right along the repeated pattern
+        let config = Config{
+            filename: String::from("_"),
+            slopes: vec![
+                Slope::parse("9223372036854775807,1").unwrap(),
+                Slope::parse("-9223372036854775808,1").unwrap(),
+            ],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let result = find_blocking_trees(&pattern, &config).unwrap();
+
+        // assert
+        assert_eq!(vec![3, 3], result);
+    }
+
+    #[test]
+    fn find_blocking_trees_should_reject_slope_too_shallow_to_cross() {
+        // arrange
+        let pattern = Pattern::new(vec![String::from("#."), String::from(".#")].into_iter().map(Ok)).unwrap();
+
+        let config = Config{
+            filename: String::from("_"),
+            slopes: vec![Slope::parse("1,1/100000000000").unwrap()],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let result = find_blocking_trees(&pattern, &config);
+
+        // assert
+        let message = "'Slope' parameter Right 1, down 1/100000000000 would take 200000000000 steps to cross the map, more than 1000000";
+        assert_eq!(message, result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn find_blocking_trees_should_reject_level_slope() {
+        // arrange
+        let pattern = Pattern::new(vec![String::from("#.")].into_iter().map(Ok)).unwrap();
+
+        let config = Config{
+            filename: String::from("_"),
+            slopes: vec![Slope::new(1, 0)],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let result = find_blocking_trees(&pattern, &config);
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn read_pattern_should_read_text_input() {
+        // arrange
+        let input = Input::text("#..\n.#.\n..#\n");
+
+        // act
+        let pattern = read_pattern(&input, &mut Diagnostics::default()).unwrap();
+
+        // assert
+        assert_eq!(3, pattern.height);
+    }
+
+    #[test]
+    fn solve_should_multiply_slopes() {
+        // arrange
+        let text = vec![
+            String::from("#..."),
+            String::from(".#.."),
+            String::from(".##."),
+            String::from("...#"),
+        ].into_iter().map(Ok);
+
+        let pattern = Pattern::new(text).unwrap();
+
+        let config = Config{ 
+            filename: String::from("_"),
+            slopes: vec![
+                Slope::new(1, 1),
+                Slope::new(1, 2),
+            ],
+            render: false,
+            colour: false,
+        };
+
+        // act
+        let answer = solve(&pattern, &config).unwrap();
+
+        // assert
+        assert_eq!(common::Value::Integer(8), answer.value);
+        assert_eq!(Some(&common::Value::Integer(2)), answer.detail("Right 1, down 2"));
+    }
 }
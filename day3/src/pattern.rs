@@ -1,5 +1,4 @@
-use std::error::Error;
-use std::io;
+use common::{Diagnostics, Error, Strictness};
 
 pub enum Tile {
     Tree,
@@ -8,46 +7,71 @@ pub enum Tile {
 
 pub struct Pattern {
     rows: Vec<Vec<Tile>>,
-    width: usize,
+    pub width: usize,
     pub height: usize,
 }
 
 impl Pattern {
-    pub fn new<T>(text: T) -> Result<Pattern, Box<dyn Error>>
-    where T : Iterator<Item = Result<String, io::Error>> {
+    pub fn new<T>(text: T) -> Result<Pattern, Error>
+    where T : Iterator<Item = Result<String, Error>> {
+        Pattern::read(text, None, &mut Diagnostics::new(Strictness::Strict))
+    }
+
+    /// Reads the pattern from the lines of `filename`, reporting any row that
+    /// fails to parse to `diagnostics`.
+    pub fn read<T>(text: T, filename: Option<&str>, diagnostics: &mut Diagnostics) -> Result<Pattern, Error>
+    where T : Iterator<Item = Result<String, Error>> {
         let mut rows: Vec<Vec<Tile>> = Vec::new();
 
         let mut width = 0;
-        for line in text {
-            match line {
-                Ok(line) => {
-                    let mut row: Vec<Tile> = Vec::new();
-                    for c in line.chars() {
-                        match c {
-                            '#' => row.push(Tile::Tree),
-                            '.' => row.push(Tile::Empty),
-                            _ => return Err("Invalid character in pattern text".into()),
-                        };
-                    }
-
-                    if width == 0 {
-                        width = row.len();
-                    } else if width != row.len() {
-                        return Err("Pattern is not of a fixed width".into());
-                    }
+        for (index, line) in text.enumerate() {
+            let line = line?;
 
+            match Pattern::parse_row(&line, width) {
+                Ok(row) => {
+                    width = row.len();
                     rows.push(row);
                 },
-                Err(err) => return Err(Box::new(err)),
+                Err(err) => {
+                    let err = err.on_line(index + 1);
+                    let err = match filename {
+                        Some(filename) => err.in_file(filename),
+                        None => err,
+                    };
+                    diagnostics.report(err)?;
+                },
             }
         }
 
         let height = rows.len();
-        Ok(Pattern{ rows, width, height: height })
+        Ok(Pattern{ rows, width, height })
+    }
+
+    fn parse_row(line: &str, width: usize) -> Result<Vec<Tile>, Error> {
+        if line.is_empty() {
+            return Err(Error::parse(line, 1, "Pattern row is empty"));
+        }
+
+        let mut row: Vec<Tile> = Vec::new();
+        for (column, c) in line.chars().enumerate() {
+            match c {
+                '#' => row.push(Tile::Tree),
+                '.' => row.push(Tile::Empty),
+                _ => return Err(Error::parse(line, column + 1, "Invalid character in pattern text")),
+            };
+        }
+
+        if width != 0 && width != row.len() {
+            return Err(Error::parse(line, width.min(row.len()) + 1, "Pattern is not of a fixed width"));
+        }
+
+        Ok(row)
     }
 
-    pub fn get_tile(&self, x: usize, y: usize) -> &Tile {
-        &self.rows[y][x % self.width]
+    /// The tile at column `x` of row `y`, where the pattern repeats to the
+    /// right and, for negative `x`, to the left.
+    pub fn get_tile(&self, x: i128, y: usize) -> &Tile {
+        &self.rows[y][x.rem_euclid(self.width as i128) as usize]
     }
 }
 
@@ -63,7 +87,7 @@ mod tests {
             String::from(".#.."),
             String::from("..#."),
             String::from("...#"),
-        ].into_iter().map(|t| Ok(t));
+        ].into_iter().map(Ok);
 
         // act
         let pattern = Pattern::new(text).unwrap();
@@ -82,7 +106,7 @@ mod tests {
             String::from(".#.."),
             String::from("..#."),
             String::from("...#"),
-        ].into_iter().map(|t| Ok(t));
+        ].into_iter().map(Ok);
 
         // act
         let pattern = Pattern::new(text).unwrap();
@@ -95,4 +119,81 @@ mod tests {
         assert!(matches!(pattern.get_tile(8, 0), Tile::Tree));
         assert!(matches!(pattern.get_tile(8, 1), Tile::Empty));
     }
+
+    #[test]
+    fn get_tile_should_repeat_pattern_to_the_left() {
+        // arrange
+        let text = vec![
+            String::from("#..."),
+            String::from("...#"),
+        ].into_iter().map(Ok);
+
+        // act
+        let pattern = Pattern::new(text).unwrap();
+
+        // assert
+        assert!(matches!(pattern.get_tile(-1, 1), Tile::Tree));
+        assert!(matches!(pattern.get_tile(-4, 0), Tile::Tree));
+        assert!(matches!(pattern.get_tile(-5, 0), Tile::Empty));
+        assert!(matches!(pattern.get_tile(-9, 1), Tile::Tree));
+    }
+
+    #[test]
+    fn new_should_locate_invalid_character() {
+        // arrange
+        let text = vec![
+            String::from("#..."),
+            String::from(".#x."),
+        ].into_iter().map(Ok);
+
+        // act
+        let result = Pattern::new(text);
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!((2, 3), (diagnostic.line, diagnostic.column)),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn new_should_reject_leading_empty_row() {
+        // arrange
+        let text = vec![
+            String::new(),
+            String::from("#.."),
+            String::from(".#."),
+        ].into_iter().map(Ok);
+
+        // act
+        let result = Pattern::new(text);
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => {
+                assert_eq!((1, 1), (diagnostic.line, diagnostic.column));
+                assert_eq!("Pattern row is empty", diagnostic.message);
+            },
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn new_should_locate_ragged_row() {
+        // arrange
+        let text = vec![
+            String::from("#.."),
+            String::from(".#."),
+            String::from("..#."),
+        ].into_iter().map(Ok);
+
+        // act
+        let result = Pattern::new(text);
+
+        // assert
+        match result {
+            Err(Error::Parse(diagnostic)) => assert_eq!((3, 4), (diagnostic.line, diagnostic.column)),
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use common::Error;
+
+use crate::pattern::{Pattern, Tile};
+use crate::slope::Slope;
+
+/// ANSI foreground colours given to each slope's path in turn.
+const COLOURS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+
+/// Widest map drawn, as steep slopes can reach far beyond any terminal.
+const MAX_COLUMNS: i128 = 100_000;
+
+/// Draws the map repeated out to every column the paths reach, marking each
+/// tile a path visits with `O` for a tree and `X` for an open square, as in
+/// the puzzle statement. Tiles visited by several paths show the first.
+/// With `colour`, each path is drawn in its own ANSI colour under a legend.
+pub fn map(pattern: &Pattern, slopes: &[Slope], paths: &[Vec<(i128, usize)>], colour: bool) -> Result<String, Error> {
+    let width = pattern.width as i128;
+
+    let mut visits: HashMap<(i128, usize), usize> = HashMap::new();
+    for (index, path) in paths.iter().enumerate() {
+        for &position in path {
+            visits.entry(position).or_insert(index);
+        }
+    }
+
+    // Whole copies of the pattern, from the leftmost to the rightmost visit
+    let xs = || paths.iter().flatten().map(|&(x, _)| x);
+    let left = xs().min().unwrap_or(0).min(0).div_euclid(width) * width;
+    let right = (xs().max().unwrap_or(0).div_euclid(width) + 1).max(1) * width;
+    if right - left > MAX_COLUMNS {
+        let message = format!("would be {} columns wide, more than {}", right - left, MAX_COLUMNS);
+        return Err(Error::invalid_argument("Render", &message));
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+
+    if colour {
+        for (index, slope) in slopes.iter().enumerate() {
+            lines.push(format!("{} {}", paint("O", index), slope));
+        }
+        lines.push(String::new());
+    }
+
+    for y in 0..pattern.height {
+        let mut line = String::new();
+
+        for x in left..right {
+            let tile = pattern.get_tile(x, y);
+
+            match visits.get(&(x, y)) {
+                Some(&index) => {
+                    let mark = if let Tile::Tree = tile { "O" } else { "X" };
+                    if colour {
+                        line.push_str(&paint(mark, index));
+                    } else {
+                        line.push_str(mark);
+                    }
+                },
+                None => line.push(if let Tile::Tree = tile { '#' } else { '.' }),
+            }
+        }
+
+        lines.push(line);
+    }
+
+    Ok(lines.join("\n"))
+}
+
+fn paint(text: &str, index: usize) -> String {
+    format!("\x1b[{}m{}\x1b[0m", COLOURS[index % COLOURS.len()], text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern() -> Pattern {
+        let text = vec![
+            String::from("#.."),
+            String::from(".#."),
+            String::from("..#"),
+        ].into_iter().map(Ok);
+
+        Pattern::new(text).unwrap()
+    }
+
+    #[test]
+    fn map_should_overlay_path_on_repeated_pattern() {
+        // arrange
+        let slopes = vec![Slope::new(2, 1)];
+        let paths = vec![vec![(0, 0), (2, 1), (4, 2)]];
+
+        // act
+        let text = map(&pattern(), &slopes, &paths, false).unwrap();
+
+        // assert
+        assert_eq!("O..#..\n.#X.#.\n..#.X#", text);
+    }
+
+    #[test]
+    fn map_should_extend_left_and_colour_paths() {
+        // arrange
+        let slopes = vec![Slope::new(-1, 1), Slope::new(1, 1)];
+        let paths = vec![vec![(0, 0), (-1, 1), (-2, 2)], vec![(0, 0), (1, 1), (2, 2)]];
+
+        // act
+        let text = map(&pattern(), &slopes, &paths, true).unwrap();
+
+        // assert
+        let red = |mark: &str| format!("\x1b[31m{}\x1b[0m", mark);
+        let green = |mark: &str| format!("\x1b[32m{}\x1b[0m", mark);
+        let expected = [
+            format!("{} Left 1, down 1", red("O")),
+            format!("{} Right 1, down 1", green("O")),
+            String::new(),
+            format!("#..{}..", red("O")),
+            format!(".#{}.{}.", red("X"), green("O")),
+            format!(".{}#..{}", red("X"), green("O")),
+        ];
+        assert_eq!(expected.join("\n"), text);
+    }
+
+    #[test]
+    fn map_should_reject_paths_too_wide_to_draw() {
+        // arrange
+        let slopes = [Slope::new(i64::MAX, 1)];
+        let paths = vec![vec![(0, 0), (i64::MAX.into(), 1)]];
+
+        // act
+        let result = map(&pattern(), &slopes, &paths, false);
+
+        // assert
+        assert_eq!("'Render' parameter would be 9223372036854775809 columns wide, more than 100000", result.unwrap_err().to_string());
+    }
+}
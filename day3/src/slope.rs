@@ -0,0 +1,196 @@
+use std::fmt;
+
+use common::Error;
+
+/// A signed fraction in lowest terms, with a positive denominator. Parts are
+/// read as `i64` but held as `i128`, so that negating or multiplying them by
+/// a step count cannot overflow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fraction {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Fraction {
+    pub fn new(numerator: i128, denominator: i128) -> Fraction {
+        let divisor = gcd(numerator, denominator).max(1) * denominator.signum();
+        Fraction { numerator: numerator / divisor, denominator: denominator / divisor }
+    }
+
+    /// Reads an integer such as `-3` or a fraction such as `1/2`.
+    fn parse(name: &'static str, text: &str) -> Result<Fraction, Error> {
+        let mut parts = text.splitn(2, '/');
+        let numerator = parts.next().unwrap_or_default().trim();
+        let numerator: i64 = numerator.parse().map_err(|e| Error::bad_integer(name, numerator, e))?;
+
+        let denominator: i64 = match parts.next() {
+            Some(denominator) => denominator.trim().parse().map_err(|e| Error::bad_integer(name, denominator, e))?,
+            None => 1,
+        };
+        if denominator == 0 {
+            return Err(Error::invalid_argument(name, &format!("must not divide by zero, got '{}'", text)));
+        }
+
+        Ok(Fraction::new(numerator.into(), denominator.into()))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    fn is_negative(&self) -> bool {
+        self.numerator < 0
+    }
+
+    fn abs(&self) -> Fraction {
+        Fraction { numerator: self.numerator.abs(), denominator: self.denominator }
+    }
+
+    /// The whole number of tiles moved after `steps` steps, rounded towards
+    /// negative infinity so that moving left from 0 lands on tile -1.
+    fn floor_times(&self, steps: i64) -> i128 {
+        (self.numerator * i128::from(steps)).div_euclid(self.denominator)
+    }
+}
+
+impl fmt::Display for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// How far the sled moves across and down the map with each step. Negative
+/// `x_move` goes left and negative `y_move` goes up, starting from the bottom
+/// row. Fractions move part of a tile per step, the sled being on whichever
+/// tile holds its position, so `1/2,1` visits every row where `1,2` skips
+/// every other one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slope {
+    pub x_move: Fraction,
+    pub y_move: Fraction,
+}
+
+impl Slope {
+    pub fn new(x_move: i64, y_move: i64) -> Slope {
+        Slope { x_move: Fraction::new(x_move.into(), 1), y_move: Fraction::new(y_move.into(), 1) }
+    }
+
+    /// Reads a slope written as `X,Y`, where each may be a fraction.
+    pub fn parse(text: &str) -> Result<Slope, Error> {
+        let pair: Vec<&str> = text.split(',').collect();
+
+        if pair.len() != 2 {
+            let message = format!("must use the format '{{X}},{{Y}}', got '{}'", text);
+            return Err(Error::invalid_argument("Slope", &message));
+        }
+
+        let slope = Slope {
+            x_move: Fraction::parse("X-Move", pair[0])?,
+            y_move: Fraction::parse("Y-Move", pair[1])?,
+        };
+        slope.validate()?;
+
+        Ok(slope)
+    }
+
+    /// Rejects slopes that never leave the map.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.y_move.is_zero() {
+            return Err(Error::invalid_argument("Slope", &format!("must move up or down, got {}", self)));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the sled climbs from the bottom row rather than descending
+    /// from the top.
+    pub fn is_upward(&self) -> bool {
+        self.y_move.is_negative()
+    }
+
+    /// How many steps the sled takes to move `rows` rows up or down, rounded
+    /// up. The slope must move up or down.
+    pub fn steps_across(&self, rows: usize) -> i128 {
+        let Fraction { numerator, denominator } = self.y_move;
+        let rows = rows as i128 * denominator;
+
+        (rows + numerator.abs() - 1) / numerator.abs()
+    }
+
+    /// The tile the sled is on after `steps` steps, relative to where it
+    /// started.
+    pub fn position(&self, steps: i64) -> (i128, i128) {
+        (self.x_move.floor_times(steps), self.y_move.floor_times(steps))
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let across = if self.x_move.is_negative() { "Left" } else { "Right" };
+        let down = if self.y_move.is_negative() { "up" } else { "down" };
+
+        write!(f, "{} {}, {} {}", across, self.x_move.abs(), down, self.y_move.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_should_read_signed_fractions() {
+        // arrange
+        // act
+        let slope = Slope::parse("-2/4,3").unwrap();
+
+        // assert
+        assert_eq!(Fraction::new(-1, 2), slope.x_move);
+        assert_eq!("Left 1/2, down 3", slope.to_string());
+        assert_eq!(vec![(0, 0), (-1, 3), (-1, 6), (-2, 9)], (0..4).map(|s| slope.position(s)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parse_should_reject_zero_vertical_movement() {
+        // arrange
+        // act
+        let result = Slope::parse("3,0/2");
+
+        // assert
+        match result {
+            Err(err) => assert_eq!("'Slope' parameter must move up or down, got Right 3, down 0", err.to_string()),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn parse_should_reject_zero_denominator() {
+        // arrange
+        // act
+        let result = Slope::parse("1/0,1");
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_should_hold_extreme_moves() {
+        // arrange
+        // act
+        let right = Slope::parse("9223372036854775807,1").unwrap();
+        let left = Slope::parse("-9223372036854775808,1").unwrap();
+
+        // assert
+        assert_eq!("Right 9223372036854775807, down 1", right.to_string());
+        assert_eq!("Left 9223372036854775808, down 1", left.to_string());
+        assert_eq!((2 * i128::from(i64::MAX), 2), right.position(2));
+        assert_eq!((2 * i128::from(i64::MIN), 2), left.position(2));
+    }
+}